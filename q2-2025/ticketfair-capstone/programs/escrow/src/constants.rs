@@ -1,27 +1,25 @@
 // Constants for the TicketFair Dutch auction program
 
-// Account status constants
-pub const EVENT_STATUS_CREATED: u8 = 0;
-pub const EVENT_STATUS_ACTIVE: u8 = 1;
-pub const EVENT_STATUS_FINALIZED: u8 = 2;
-pub const EVENT_STATUS_CANCELLED: u8 = 3;
-
-pub const BID_STATUS_PENDING: u8 = 0;
-pub const BID_STATUS_AWARDED: u8 = 1;
-pub const BID_STATUS_REFUNDED: u8 = 2;
-
-pub const TICKET_STATUS_OWNED: u8 = 0;
-pub const TICKET_STATUS_CLAIMED: u8 = 1;
-pub const TICKET_STATUS_REFUNDED: u8 = 2;
-
 // Auction parameter constants
 pub const MAX_METADATA_URL_LEN: usize = 200;
 pub const MAX_TICKETS_PER_EVENT: u32 = 1000;
 pub const MAX_TICKETS_TEST_MODE: u32 = 10; // Reduced for tests
+pub const MAX_TIERS_PER_EVENT: usize = 4; // Ticket tiers, each with its own supply and price
+pub const TICKETS_PER_INVENTORY_PAGE: u32 = 100; // cNFT asset IDs per ticket inventory account
 
-// Time constants (in seconds)
+// Time constants (in seconds), the auction durations are `ProtocolLimits` defaults
 pub const MIN_AUCTION_DURATION: i64 = 300; // 5 minutes
 pub const MAX_AUCTION_DURATION: i64 = 2592000; // 30 days
+pub const DEFAULT_CHECK_IN_OPENS_BEFORE: i64 = 21600; // 6 hours before the event starts
+pub const DEFAULT_CHECK_IN_CLOSES_AFTER: i64 = 43200; // 12 hours after the event starts
+pub const MAX_CHECK_IN_WINDOW: i64 = 604800; // 7 days either side of the event start
+
+// Resale constants (in basis points)
+pub const DEFAULT_RESALE_CAP_BPS: u16 = 11_000; // 110% of the auction close price
+pub const DEFAULT_RESALE_ROYALTY_BPS: u16 = 500; // 5% to the organizer
+
+// Price constants (in lamports), the `ProtocolLimits` default
+pub const MIN_TICKET_PRICE: u64 = 1_000_000; // 0.001 SOL
 
-// Price constants (in lamports)
-pub const MIN_TICKET_PRICE: u64 = 1_000_000; // 0.001 SOL
\ No newline at end of file
+// Protocol fee constants (in basis points)
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 1_000; // 10% of event proceeds
\ No newline at end of file
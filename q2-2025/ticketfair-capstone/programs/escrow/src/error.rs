@@ -10,6 +10,168 @@ pub enum ErrorCode {
     AuctionNotStarted,
     #[msg("Auction has already ended.")]
     AuctionEnded,
-    #[msg("Bid must be exactly equal to the current auction price.")]
-    BidNotAtCurrentPrice,
+    #[msg("Maximum bid price is below the current auction price.")]
+    BidBelowCurrentPrice,
+    #[msg("Bubblegum program account does not match the expected program ID.")]
+    InvalidBubblegumProgram,
+    #[msg("Tree config account is not the Bubblegum tree config for this Merkle tree.")]
+    InvalidTreeConfig,
+    #[msg("The event PDA must be the tree delegate of the Merkle tree.")]
+    TreeDelegateMismatch,
+    #[msg("The cNFT asset does not belong to this event.")]
+    CnftAssetNotInEvent,
+    #[msg("The cNFT asset has already been awarded.")]
+    CnftAssetAlreadyAwarded,
+    #[msg("Event start time must not be before the auction end time.")]
+    InvalidEventStartTime,
+    #[msg("Event can only be cancelled before check-in opens and before proceeds are withdrawn.")]
+    EventCannotBeCancelled,
+    #[msg("The ticket account is required to refund an awarded bid.")]
+    TicketAccountRequired,
+    #[msg("Ticket cannot be refunded.")]
+    TicketNotRefundable,
+    #[msg("Auction has not been finalized.")]
+    EventNotFinalized,
+    #[msg("There are no proceeds left to withdraw.")]
+    NoProceedsToWithdraw,
+    #[msg("Escrow balance cannot cover outstanding refunds.")]
+    InsufficientEscrowBalance,
+    #[msg("Token payment accounts are required for events paid in a token mint.")]
+    PaymentAccountsRequired,
+    #[msg("Payment mint does not match the event's payment mint.")]
+    InvalidPaymentMint,
+    #[msg("Price curve parameters are invalid.")]
+    InvalidPriceCurve,
+    #[msg("Signer is not the gate authority for this event.")]
+    InvalidGateAuthority,
+    #[msg("Check-in window parameters are invalid.")]
+    InvalidCheckInWindow,
+    #[msg("Check-in is not open for this event.")]
+    CheckInNotOpen,
+    #[msg("The ticket does not belong to this event.")]
+    TicketNotForEvent,
+    #[msg("Ticket has already been checked in.")]
+    TicketAlreadyCheckedIn,
+    #[msg("Ticket cannot be checked in.")]
+    TicketNotClaimable,
+    #[msg("Resale terms are invalid.")]
+    InvalidResaleTerms,
+    #[msg("Resale is only open once the event can no longer be cancelled, until it starts.")]
+    ResaleNotOpen,
+    #[msg("Ticket cannot be listed for resale.")]
+    TicketNotListable,
+    #[msg("Resale price must be above zero and at most the organizer's cap.")]
+    ResalePriceAboveCap,
+    #[msg("All tickets for this event have been sold.")]
+    TicketsSoldOut,
+    #[msg("Per-wallet ticket limit must be between 1 and the ticket supply.")]
+    InvalidWalletLimit,
+    #[msg("Quantity must be at least 1 and within the event's per-wallet ticket limit.")]
+    WalletLimitExceeded,
+    #[msg("Presale parameters are invalid.")]
+    InvalidPresale,
+    #[msg("The presale can no longer be changed once it has started.")]
+    PresaleAlreadyStarted,
+    #[msg("Wallet is not on the presale allowlist.")]
+    NotOnAllowlist,
+    #[msg("Bid attestation is missing: an ed25519 signature instruction must come right before place_bid.")]
+    AttestationMissing,
+    #[msg("Bid attestation is not signed by the event's attestation issuer.")]
+    InvalidAttestationSigner,
+    #[msg("Bid attestation is not for this event and bidder.")]
+    AttestationMismatch,
+    #[msg("Bid attestation has expired.")]
+    AttestationExpired,
+    #[msg("Event cannot move from its current status to the requested one.")]
+    InvalidEventStatusTransition,
+    #[msg("Bid cannot move from its current status to the requested one.")]
+    InvalidBidStatusTransition,
+    #[msg("Ticket cannot move from its current status to the requested one.")]
+    InvalidTicketStatusTransition,
+    #[msg("Only the event organizer can perform this action.")]
+    NotEventOrganizer,
+    #[msg("The bid does not belong to this event.")]
+    BidNotForEvent,
+    #[msg("Every ticket in the bid has already been awarded.")]
+    BidFullyAwarded,
+    #[msg("Bid has already been refunded.")]
+    BidAlreadyRefunded,
+    #[msg("Auction has not ended yet.")]
+    AuctionNotEnded,
+    #[msg("The bid does not belong to this bidder.")]
+    BidderMismatch,
+    #[msg("Merkle tree does not match the event's Merkle tree.")]
+    MerkleTreeMismatch,
+    #[msg("The ticket is not owned by this wallet.")]
+    TicketOwnerMismatch,
+    #[msg("The listing was not created by this seller.")]
+    NotListingSeller,
+    #[msg("The listing is not for this ticket.")]
+    ListingNotForTicket,
+    #[msg("The ticket inventory page is not for this event.")]
+    InventoryNotForEvent,
+    #[msg("The ticket inventory page is full.")]
+    TicketInventoryPageFull,
+    #[msg("Every ticket must be minted before the event can be activated.")]
+    TicketInventoryIncomplete,
+    #[msg("Invalid number of tickets to mint.")]
+    InvalidMintCount,
+    #[msg("The bid cannot be closed while funds may still be owed on it.")]
+    BidNotClosable,
+    #[msg("The ticket cannot be closed yet.")]
+    TicketNotClosable,
+    #[msg("Rent payer does not match the ticket's rent payer.")]
+    RentPayerMismatch,
+    #[msg("This instruction does not apply to the event's auction type.")]
+    AuctionTypeMismatch,
+    #[msg("Invalid auction type.")]
+    InvalidAuctionType,
+    #[msg("Deposit does not cover the bid.")]
+    DepositTooLow,
+    #[msg("Sealed bids cannot be revealed now.")]
+    RevealNotOpen,
+    #[msg("Revealed price and salt do not match the bid's commitment.")]
+    CommitmentMismatch,
+    #[msg("Bid is below the reserve price.")]
+    BidBelowReservePrice,
+    #[msg("Sealed bids can only be awarded once revealed, after the reveal window closes.")]
+    BidNotRevealed,
+    #[msg("The event has no ticket tier with this index.")]
+    InvalidTier,
+    #[msg("Invalid ticket tiers.")]
+    InvalidTicketTiers,
+    #[msg("The bid is not for this ticket tier.")]
+    BidNotForTier,
+    #[msg("The cNFT asset is not one of this ticket tier's tickets.")]
+    CnftAssetNotInTier,
+    #[msg("Only the program's upgrade authority can create the protocol config.")]
+    NotUpgradeAuthority,
+    #[msg("Only the protocol admin can perform this action.")]
+    NotProtocolAdmin,
+    #[msg("Only the proposed protocol admin can accept the handover.")]
+    NotPendingProtocolAdmin,
+    #[msg("Protocol fee is above the maximum.")]
+    InvalidProtocolFee,
+    #[msg("Invalid protocol limits.")]
+    InvalidProtocolLimits,
+    #[msg("Treasury does not match the protocol config's treasury.")]
+    TreasuryMismatch,
+    #[msg("The cNFT leaf being transferred is not the ticket's asset.")]
+    CnftAssetMismatch,
+    #[msg("The event has not been cancelled.")]
+    EventNotCancelled,
+    #[msg("Enough higher revealed bids fill the tier for this bid to be awarded.")]
+    BidOutranked,
+    #[msg("Ticket price is below the protocol's minimum.")]
+    TicketPriceBelowMinimum,
+    #[msg("Ticket supply is above the protocol's maximum.")]
+    TicketSupplyAboveMaximum,
+    #[msg("Auction duration is outside the protocol's limits.")]
+    AuctionDurationOutOfRange,
+    #[msg("Payment mints with a Token-2022 transfer fee are not supported.")]
+    UnsupportedPaymentMint,
+    #[msg("Merkle tree must be allocated for the compression program and sized for the ticket supply.")]
+    InvalidMerkleTreeAccount,
+    #[msg("The bid ranking account is required for sealed-bid auctions.")]
+    BidRankingRequired,
 }
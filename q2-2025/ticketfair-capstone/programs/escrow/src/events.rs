@@ -0,0 +1,296 @@
+//! Ticketfair events emitted over the auction lifecycle, so off-chain services can rebuild history from logs
+
+use anchor_lang::prelude::*;
+use crate::state::{AuctionType, BidStatus, EventStatus, ProtocolLimits, TicketStatus, TicketTier};
+
+#[event]
+pub struct EventCreated {
+    pub event: Pubkey,
+    pub organizer: Pubkey,
+    pub event_id: u32,
+    pub ticket_supply: u32,
+    pub max_tickets_per_wallet: u32,
+    pub tiers: Vec<TicketTier>,
+    pub auction_start_time: i64,
+    pub auction_end_time: i64,
+    pub payment_mint: Option<Pubkey>,
+    pub status: EventStatus,
+}
+
+#[event]
+pub struct TicketsMinted {
+    pub event: Pubkey,
+    pub inventory: Pubkey,
+    pub page: u32,
+    pub count: u32,
+    /// Tickets minted across every page so far
+    pub tickets_minted: u32,
+}
+
+#[event]
+pub struct EventActivated {
+    pub event: Pubkey,
+    pub organizer: Pubkey,
+    pub status: EventStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidPlaced {
+    pub event: Pubkey,
+    pub bidder: Pubkey,
+    pub bid: Pubkey,
+    pub tier: u8,
+    /// Amount escrowed, the auction price at the time of the bid for every ticket in it
+    pub amount: u64,
+    pub quantity: u32,
+    /// Highest price the bidder agreed to pay
+    pub max_price: u64,
+    /// Whether the bid was placed in the allowlist presale
+    pub presale: bool,
+    pub status: BidStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidCommitted {
+    pub event: Pubkey,
+    pub bidder: Pubkey,
+    pub bid: Pubkey,
+    pub tier: u8,
+    /// Amount escrowed, at least the revealed price for every ticket in the bid
+    pub deposit: u64,
+    pub quantity: u32,
+    pub commitment: [u8; 32],
+    pub status: BidStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidRevealed {
+    pub event: Pubkey,
+    pub bidder: Pubkey,
+    pub bid: Pubkey,
+    /// Price bid per ticket
+    pub price: u64,
+    pub quantity: u32,
+    pub status: BidStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketAwarded {
+    pub event: Pubkey,
+    pub bidder: Pubkey,
+    pub bid: Pubkey,
+    pub ticket: Pubkey,
+    pub tier: u8,
+    pub cnft_asset_id: Pubkey,
+    /// Price paid for this ticket
+    pub amount: u64,
+    pub tickets_awarded: u32,
+    pub status: BidStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionFinalized {
+    pub event: Pubkey,
+    /// Close price of every tier, in tier order
+    pub close_prices: Vec<u64>,
+    pub tickets_awarded: u32,
+    pub status: EventStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EventCancelled {
+    pub event: Pubkey,
+    pub organizer: Pubkey,
+    pub status: EventStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidRefunded {
+    pub event: Pubkey,
+    pub bidder: Pubkey,
+    pub bid: Pubkey,
+    pub refund_amount: u64,
+    /// Total refunded to the bidder so far, including this refund
+    pub refunded_amount: u64,
+    /// Whether the bid is fully settled and closed
+    pub settled: bool,
+    /// Close price of the bid's tier
+    pub close_price: u64,
+    pub status: BidStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BidClosed {
+    pub event: Pubkey,
+    pub bidder: Pubkey,
+    pub bid: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProceedsWithdrawn {
+    pub event: Pubkey,
+    pub organizer: Pubkey,
+    /// Proceeds withdrawn, including the protocol fee
+    pub amount: u64,
+    /// Part of `amount` paid to the protocol treasury
+    pub protocol_fee: u64,
+    pub total_withdrawn: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketBought {
+    pub event: Pubkey,
+    pub buyer: Pubkey,
+    pub ticket: Pubkey,
+    pub tier: u8,
+    pub cnft_asset_id: Pubkey,
+    /// Fixed sale price, the tier's auction close price
+    pub price: u64,
+    pub tickets_awarded: u32,
+    pub status: TicketStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GateAuthoritySet {
+    pub event: Pubkey,
+    pub gate_authority: Pubkey,
+    pub check_in_opens_before: i64,
+    pub check_in_closes_after: i64,
+}
+
+#[event]
+pub struct TicketCheckedIn {
+    pub event: Pubkey,
+    pub ticket: Pubkey,
+    pub owner: Pubkey,
+    pub gate_authority: Pubkey,
+    pub status: TicketStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ResaleTermsSet {
+    pub event: Pubkey,
+    pub resale_cap_bps: u16,
+    pub resale_royalty_bps: u16,
+}
+
+#[event]
+pub struct TicketListed {
+    pub event: Pubkey,
+    pub ticket: Pubkey,
+    pub listing: Pubkey,
+    pub seller: Pubkey,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketDelisted {
+    pub event: Pubkey,
+    pub ticket: Pubkey,
+    pub seller: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketResold {
+    pub event: Pubkey,
+    /// The buyer's new ticket account
+    pub ticket: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub cnft_asset_id: Pubkey,
+    pub price: u64,
+    pub royalty: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PresaleSet {
+    pub event: Pubkey,
+    pub allowlist_root: [u8; 32],
+    pub presale_start_time: i64,
+    pub presale_discount_bps: u16,
+}
+
+#[event]
+pub struct AttestationIssuerSet {
+    pub event: Pubkey,
+    pub attestation_issuer: Option<Pubkey>,
+}
+
+#[event]
+pub struct TicketRefunded {
+    pub event: Pubkey,
+    pub ticket: Pubkey,
+    pub owner: Pubkey,
+    /// The ticket's fixed purchase price, paid back from escrow
+    pub refund_amount: u64,
+    pub status: TicketStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TicketClosed {
+    pub event: Pubkey,
+    pub ticket: Pubkey,
+    pub owner: Pubkey,
+    pub rent_payer: Pubkey,
+    pub status: TicketStatus,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionTypeSet {
+    pub event: Pubkey,
+    pub organizer: Pubkey,
+    pub auction_type: AuctionType,
+}
+
+#[event]
+pub struct ProtocolConfigCreated {
+    pub protocol_config: Pubkey,
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+    pub limits: ProtocolLimits,
+}
+
+#[event]
+pub struct ProtocolFeeSet {
+    pub protocol_config: Pubkey,
+    pub fee_bps: u16,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct ProtocolLimitsSet {
+    pub protocol_config: Pubkey,
+    pub limits: ProtocolLimits,
+}
+
+#[event]
+pub struct ProtocolAdminProposed {
+    pub protocol_config: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+}
+
+#[event]
+pub struct ProtocolAdminAccepted {
+    pub protocol_config: Pubkey,
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+}
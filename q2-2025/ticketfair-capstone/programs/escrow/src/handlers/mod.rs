@@ -21,3 +21,6 @@ pub use ticketfair_user::*;
 
 pub mod ticketfair_bid;
 pub use ticketfair_bid::*;
+
+pub mod ticketfair_protocol;
+pub use ticketfair_protocol::*;
@@ -1,9 +1,107 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{ed25519_program, sysvar::instructions::get_instruction_relative};
 
 use anchor_spl::token_interface::{
     close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
     TransferChecked,
 };
+use anchor_spl::token_interface::spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    state::Mint as MintState,
+};
+
+use crate::error::ErrorCode;
+#[cfg(feature = "bubblegum")]
+use mpl_bubblegum::instructions::{TransferV2Cpi, TransferV2CpiAccounts, TransferV2InstructionArgs};
+
+/// Leaf data needed to transfer a compressed NFT (as returned by the DAS `getAsset` / `getAssetProof` calls)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CnftTransferArgs {
+    /// Merkle root the proof was generated against
+    pub root: [u8; 32],
+    /// Hash of the cNFT metadata
+    pub data_hash: [u8; 32],
+    /// Hash of the cNFT creators
+    pub creator_hash: [u8; 32],
+    /// Leaf nonce (used to derive the asset ID)
+    pub nonce: u64,
+    /// Leaf index in the Merkle tree
+    pub index: u32,
+}
+
+/// Length of a bid attestation message: event (32) || bidder (32) || expiry (8, little-endian)
+pub const ATTESTATION_MESSAGE_LEN: usize = 32 + 32 + 8;
+
+// Size of the Ed25519SignatureOffsets struct in an ed25519 program instruction
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+const ED25519_PUBKEY_LEN: usize = 32;
+
+/// Read the signer and message out of a single-signature ed25519 program instruction.
+/// Only signatures whose public key and message are stored in the instruction itself are accepted.
+pub fn parse_ed25519_instruction(data: &[u8]) -> Option<(Pubkey, &[u8])> {
+    if data.len() < ED25519_OFFSETS_START + ED25519_OFFSETS_LEN || data[0] != 1 {
+        return None;
+    }
+    let read_u16 = |at: usize| -> usize {
+        u16::from_le_bytes([data[ED25519_OFFSETS_START + at], data[ED25519_OFFSETS_START + at + 1]]) as usize
+    };
+    let signature_instruction_index = read_u16(2);
+    let public_key_offset = read_u16(4);
+    let public_key_instruction_index = read_u16(6);
+    let message_data_offset = read_u16(8);
+    let message_data_size = read_u16(10);
+    let message_instruction_index = read_u16(12);
+
+    // u16::MAX means "this instruction"; anything else could point the check at unrelated data
+    let this_instruction = u16::MAX as usize;
+    if signature_instruction_index != this_instruction
+        || public_key_instruction_index != this_instruction
+        || message_instruction_index != this_instruction
+    {
+        return None;
+    }
+
+    let public_key = data.get(public_key_offset..public_key_offset.checked_add(ED25519_PUBKEY_LEN)?)?;
+    let message = data.get(message_data_offset..message_data_offset.checked_add(message_data_size)?)?;
+    Some((Pubkey::try_from(public_key).ok()?, message))
+}
+
+/// Require the instruction right before this one to be an ed25519 signature by `issuer` over
+/// `(event, bidder, expiry)`, with `expiry` not yet passed.
+pub fn verify_bid_attestation(
+    instructions_sysvar: &AccountInfo,
+    issuer: &Pubkey,
+    event: &Pubkey,
+    bidder: &Pubkey,
+    now: i64,
+) -> Result<()> {
+    let instruction = get_instruction_relative(-1, instructions_sysvar)
+        .map_err(|_| error!(ErrorCode::AttestationMissing))?;
+    if instruction.program_id != ed25519_program::ID {
+        return Err(error!(ErrorCode::AttestationMissing));
+    }
+    let (signer, message) = parse_ed25519_instruction(&instruction.data)
+        .ok_or(error!(ErrorCode::AttestationMissing))?;
+
+    if signer != *issuer {
+        return Err(error!(ErrorCode::InvalidAttestationSigner));
+    }
+    if message.len() != ATTESTATION_MESSAGE_LEN
+        || message[..32] != event.to_bytes()
+        || message[32..64] != bidder.to_bytes()
+    {
+        return Err(error!(ErrorCode::AttestationMismatch));
+    }
+
+    let mut expiry_bytes = [0u8; 8];
+    expiry_bytes.copy_from_slice(&message[64..]);
+    if now > i64::from_le_bytes(expiry_bytes) {
+        return Err(error!(ErrorCode::AttestationExpired));
+    }
+
+    Ok(())
+}
 
 // Transfer tokens from one account to another
 // If transferring from a token account owned by a PDA, owning_pda_seeds must be provided.
@@ -41,6 +139,110 @@ pub fn transfer_tokens<'info>(
     )
 }
 
+// Transfer lamports from one system account to another
+// If transferring from a system account that is a PDA, owning_pda_seeds must be provided.
+pub fn transfer_lamports<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+    system_program: &AccountInfo<'info>,
+    owning_pda_seeds: Option<&[&[u8]]>,
+) -> Result<()> {
+    let transfer_accounts = anchor_lang::system_program::Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+    };
+
+    // Only one signer seed (the PDA that holds the lamports) is needed, so we create an array with the seeds
+    let signers_seeds = owning_pda_seeds.map(|seeds| [seeds]);
+
+    anchor_lang::system_program::transfer(
+        if let Some(seeds_arr) = signers_seeds.as_ref() {
+            CpiContext::new_with_signer(system_program.to_account_info(), transfer_accounts, seeds_arr)
+        } else {
+            CpiContext::new(system_program.to_account_info(), transfer_accounts)
+        },
+        amount,
+    )
+}
+
+/// Token accounts used to move a payment when the event is paid in an SPL Token / Token-2022 mint
+pub struct TokenPaymentAccounts<'a, 'info> {
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub from: &'a InterfaceAccount<'info, TokenAccount>,
+    pub to: &'a InterfaceAccount<'info, TokenAccount>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+}
+
+impl<'a, 'info> TokenPaymentAccounts<'a, 'info> {
+    /// Collect the optional token accounts of an instruction, if they were all passed
+    pub fn from_optional(
+        mint: &'a Option<Box<InterfaceAccount<'info, Mint>>>,
+        from: &'a Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        to: &'a Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+        token_program: &'a Option<Interface<'info, TokenInterface>>,
+    ) -> Option<Self> {
+        Some(Self {
+            mint: mint.as_deref()?,
+            from: from.as_deref()?,
+            to: to.as_deref()?,
+            token_program: token_program.as_ref()?,
+        })
+    }
+}
+
+// Move an event payment: lamports between system accounts for native SOL events,
+// or tokens with transfer_checked when the event names a payment mint.
+// `from` is the paying system account, or the authority of the source token account.
+// If paying from a PDA, owning_pda_seeds must be provided.
+pub fn transfer_payment<'info>(
+    payment_mint: Option<Pubkey>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    token_accounts: Option<TokenPaymentAccounts<'_, 'info>>,
+    amount: u64,
+    system_program: &AccountInfo<'info>,
+    owning_pda_seeds: Option<&[&[u8]]>,
+) -> Result<()> {
+    let Some(payment_mint) = payment_mint else {
+        return transfer_lamports(from, to, amount, system_program, owning_pda_seeds);
+    };
+
+    let token_accounts = token_accounts.ok_or(error!(ErrorCode::PaymentAccountsRequired))?;
+    if token_accounts.mint.key() != payment_mint {
+        return Err(error!(ErrorCode::InvalidPaymentMint));
+    }
+
+    transfer_tokens(
+        token_accounts.from,
+        token_accounts.to,
+        &amount,
+        token_accounts.mint,
+        from,
+        token_accounts.token_program,
+        owning_pda_seeds,
+    )
+}
+
+/// Check a payment mint always moves the full amount of a transfer. The escrow records what it is sent,
+/// so a Token-2022 transfer fee would leave it short of the refunds and proceeds it owes.
+pub fn is_supported_payment_mint(mint_data: &[u8]) -> bool {
+    let Ok(mint) = StateWithExtensions::<MintState>::unpack(mint_data) else {
+        return false;
+    };
+    mint.get_extension_types()
+        .is_ok_and(|extensions| !extensions.contains(&ExtensionType::TransferFeeConfig))
+}
+
+/// Lamports a native SOL escrow PDA keeps so it stays rent-exempt between payments (0 for token escrows,
+/// whose vault is a rent-exempt token account of its own). Paid by the organizer when the event is created.
+pub fn escrow_rent_reserve(payment_mint: Option<Pubkey>) -> Result<u64> {
+    match payment_mint {
+        Some(_) => Ok(0),
+        None => Ok(Rent::get()?.minimum_balance(0)),
+    }
+}
+
 // Close a token account and send the rent to the specified destination
 // If the token account is owned by a PDA, owning_pda_seeds must be provided.
 pub fn close_token_account<'info>(
@@ -66,9 +268,107 @@ pub fn close_token_account<'info>(
     })
 }
 
-/// Derive the event PDA for a given organizer pubkey
-pub fn derive_event_pda(organizer: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"event", organizer.as_ref()], program_id)
+// Transfer a compressed NFT from its current leaf owner to a new owner via Bubblegum transfer_v2
+// The Merkle proof path is passed as `proof` (usually the instruction's remaining accounts).
+// The leaf in `transfer_args` must be `asset_id`, so the asset recorded on-chain is the one that moves.
+// If the current leaf owner is a PDA, owning_pda_seeds must be provided.
+#[cfg(feature = "bubblegum")]
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_cnft<'info>(
+    bubblegum_program: &AccountInfo<'info>,
+    tree_config: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    leaf_owner: &AccountInfo<'info>,
+    new_leaf_owner: &AccountInfo<'info>,
+    merkle_tree: &AccountInfo<'info>,
+    log_wrapper: &AccountInfo<'info>,
+    compression_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    proof: &[AccountInfo<'info>],
+    transfer_args: &CnftTransferArgs,
+    asset_id: &Pubkey,
+    owning_pda_seeds: Option<&[&[u8]]>,
+) -> Result<()> {
+    if bubblegum_program.key() != mpl_bubblegum::ID {
+        return Err(error!(ErrorCode::InvalidBubblegumProgram));
+    }
+    if mpl_bubblegum::utils::get_asset_id(merkle_tree.key, transfer_args.nonce) != *asset_id {
+        return Err(error!(ErrorCode::CnftAssetMismatch));
+    }
+
+    let transfer_cpi = TransferV2Cpi::new(
+        bubblegum_program,
+        TransferV2CpiAccounts {
+            tree_config,
+            payer,
+            authority: Some(leaf_owner),
+            leaf_owner,
+            leaf_delegate: Some(leaf_owner),
+            new_leaf_owner,
+            merkle_tree,
+            core_collection: None,
+            log_wrapper,
+            compression_program,
+            system_program,
+        },
+        TransferV2InstructionArgs {
+            root: transfer_args.root,
+            data_hash: transfer_args.data_hash,
+            creator_hash: transfer_args.creator_hash,
+            asset_data_hash: None,
+            flags: None,
+            nonce: transfer_args.nonce,
+            index: transfer_args.index,
+        },
+    );
+
+    // Proof nodes are passed read-only and never sign
+    let proof_accounts: Vec<(&AccountInfo<'info>, bool, bool)> =
+        proof.iter().map(|node| (node, false, false)).collect();
+
+    let signers_seeds = owning_pda_seeds.map(|seeds| [seeds]);
+    match signers_seeds.as_ref() {
+        Some(seeds_arr) => {
+            transfer_cpi.invoke_signed_with_remaining_accounts(seeds_arr, &proof_accounts)?
+        }
+        None => transfer_cpi.invoke_signed_with_remaining_accounts(&[], &proof_accounts)?,
+    }
+    Ok(())
+}
+
+/// Derive the event PDA for a given organizer pubkey and per-organizer event ID
+pub fn derive_event_pda(organizer: &Pubkey, event_id: u32, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"event", organizer.as_ref(), event_id.to_le_bytes().as_ref()],
+        program_id,
+    )
+}
+
+// (max depth, max buffer size) pairs accepted by the account compression program, smallest first
+const MERKLE_TREE_DIMENSIONS: [(u32, u32); 17] = [
+    (3, 8), (5, 8), (6, 16), (7, 16), (8, 16), (9, 16), (10, 32), (11, 32), (12, 32),
+    (13, 32), (14, 64), (15, 64), (16, 64), (17, 64), (18, 64), (19, 64), (20, 64),
+];
+
+// Account type and header fields ahead of the tree itself
+const MERKLE_TREE_HEADER_SIZE: usize = 2 + 54;
+
+/// The smallest Merkle tree (max depth, max buffer size) with a leaf for every ticket
+pub fn merkle_tree_dimensions(ticket_supply: u32) -> Option<(u32, u32)> {
+    MERKLE_TREE_DIMENSIONS
+        .into_iter()
+        .find(|(max_depth, _)| (1u64 << max_depth) >= ticket_supply as u64)
+}
+
+/// Size of a concurrent Merkle tree account without a canopy, so proofs are always passed in full.
+/// This is the space the organizer allocates for an event's tree before `create_event`.
+pub fn merkle_tree_account_size(max_depth: u32, max_buffer_size: u32) -> usize {
+    let path_size = 32 * max_depth as usize + 32 + 4 + 4; // proof, leaf, index, padding
+    let change_log_size = 32 + 32 * max_depth as usize + 4 + 4; // root, path, index, padding
+    MERKLE_TREE_HEADER_SIZE
+        + 8 * 3 // sequence number, active index, buffer size
+        + change_log_size * max_buffer_size as usize
+        + path_size
 }
 
 /// Check if the given account is the Merkle Tree delegate (authority)
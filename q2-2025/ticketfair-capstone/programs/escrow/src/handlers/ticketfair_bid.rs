@@ -1,19 +1,25 @@
-//! Ticketfair bid instruction handlers (Dutch Auction)
+//! Ticketfair bid instruction handlers (Dutch and sealed-bid auctions)
 
 use anchor_lang::prelude::*;
-use crate::state::{Bid, Event, Ticket};
-use crate::constants::*;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{sealed_bid_commitment, transition, Bid, BidRanking, BidStatus, Event, EventStatus, Ticket, TicketInventory, TicketStatus};
 use crate::error::ErrorCode;
-
-// We'll add this import back when we properly integrate Bubblegum
-// #[cfg(feature = "bubblegum")]
-// use mpl_bubblegum::instruction as bubblegum_instruction;
+use crate::events::{BidClosed, BidCommitted, BidPlaced, BidRefunded, BidRevealed, TicketAwarded};
+use crate::handlers::shared::{transfer_payment, verify_bid_attestation, CnftTransferArgs, TokenPaymentAccounts};
+#[cfg(feature = "bubblegum")]
+use crate::handlers::shared::transfer_cnft;
 
 #[derive(Accounts)]
+#[instruction(tier: u8)]
 pub struct PlaceBidAccountConstraints<'info> {
     #[account(mut)]
     pub bidder: Signer<'info>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+    )]
     pub event: Account<'info, Event>,
     /// The PDA that will hold escrowed funds for the event
     /// Seeds: [b"escrow", event.key().as_ref()]
@@ -23,16 +29,43 @@ pub struct PlaceBidAccountConstraints<'info> {
         init,
         payer = bidder,
         space = Bid::DISCRIMINATOR.len() + Bid::INIT_SPACE,
-        seeds = [b"bid", event.key().as_ref(), bidder.key().as_ref()],
+        seeds = [b"bid", event.key().as_ref(), bidder.key().as_ref(), tier.to_le_bytes().as_ref()],
         bump
     )]
     pub bid: Account<'info, Bid>,
+    /// Payment mint, required when the event is paid in tokens
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Bidder's token account for the payment mint
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = bidder,
+        token::token_program = token_program,
+    )]
+    pub bidder_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Escrow token vault owned by the event PDA
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = event_pda,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    /// Instructions sysvar, required when the event names an attestation issuer
+    /// CHECK: Address checked against the instructions sysvar ID
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn place_bid(
     context: Context<PlaceBidAccountConstraints>,
-    amount: u64,
+    tier: u8,
+    max_price: u64,
+    quantity: u32,
+    allowlist_proof: Vec<[u8; 32]>,
 ) -> Result<()> {
     let event = &mut context.accounts.event;
     let bid = &mut context.accounts.bid;
@@ -44,67 +77,382 @@ pub fn place_bid(
     let now = clock.unix_timestamp;
 
     // Check auction status
-    if event.status != EVENT_STATUS_ACTIVE {
+    if event.status != EventStatus::Active {
         return Err(error!(ErrorCode::AuctionNotActive));
     }
-    if now < event.auction_start_time {
+    if event.is_sealed_bid() {
+        return Err(error!(ErrorCode::AuctionTypeMismatch));
+    }
+
+    // Before the auction starts, only allowlisted wallets can bid, during the presale
+    let presale = event.is_presale_open(now);
+    if presale {
+        if !event.is_on_allowlist(&bidder.key(), &allowlist_proof) {
+            return Err(error!(ErrorCode::NotOnAllowlist));
+        }
+    } else if now < event.auction_start_time {
         return Err(error!(ErrorCode::AuctionNotStarted));
     }
     if now > event.auction_end_time {
         return Err(error!(ErrorCode::AuctionEnded));
     }
 
-    // Calculate current auction price
-    let current_price = event.get_current_auction_price(now);
-    if amount != current_price {
-        return Err(error!(ErrorCode::BidNotAtCurrentPrice));
+    // Events with an attestation issuer only take bids it has signed off on, checked before any funds move
+    if let Some(issuer) = event.attestation_issuer {
+        let instructions_sysvar = context
+            .accounts
+            .instructions_sysvar
+            .as_ref()
+            .ok_or(error!(ErrorCode::AttestationMissing))?;
+        verify_bid_attestation(instructions_sysvar, &issuer, &event.key(), &bidder.key(), now)?;
     }
 
-    // Escrow funds from bidder to event PDA
-    let ix = anchor_lang::solana_program::system_instruction::transfer(
-        &bidder.key(),
-        &event_pda.key(),
+    // One bid per wallet and tier, for up to the organizer's per-wallet limit
+    if !event.is_within_wallet_limit(quantity) {
+        return Err(error!(ErrorCode::WalletLimitExceeded));
+    }
+
+    // Calculate the tier's current auction price, or its fixed presale price
+    let current_price = if presale {
+        event.presale_price(tier)?
+    } else {
+        event.get_current_auction_price(tier, now)?
+    };
+    if max_price < current_price {
+        return Err(error!(ErrorCode::BidBelowCurrentPrice));
+    }
+
+    // Only the current price is escrowed, so the price dropping between signing and landing never fails the bid
+    let amount = current_price.checked_mul(quantity as u64).ok_or(error!(ErrorCode::CustomError))?;
+
+    // Escrow funds from bidder to event PDA (or its token vault)
+    transfer_payment(
+        event.payment_mint,
+        &bidder.to_account_info(),
+        &event_pda.to_account_info(),
+        TokenPaymentAccounts::from_optional(
+            &context.accounts.payment_mint,
+            &context.accounts.bidder_token_account,
+            &context.accounts.escrow_vault,
+            &context.accounts.token_program,
+        ),
         amount,
-    );
-    anchor_lang::solana_program::program::invoke(
-        &ix,
-        &[
-            bidder.to_account_info(),
-            event_pda.to_account_info(),
-            context.accounts.system_program.to_account_info(),
-        ],
-    ).map_err(|_| error!(ErrorCode::CustomError))?;
+        &context.accounts.system_program.to_account_info(),
+        None,
+    )?;
+    event.total_escrowed = event.total_escrowed.checked_add(amount).ok_or(error!(ErrorCode::CustomError))?;
 
     // Record the bid
     bid.bidder = bidder.key();
     bid.event = event.key();
+    bid.tier = tier;
     bid.amount = amount;
-    bid.status = BID_STATUS_PENDING;
+    bid.quantity = quantity;
+    bid.tickets_awarded = 0;
+    bid.refunded_amount = 0;
+    bid.settled = false;
+    bid.commitment = [0; 32];
+    bid.revealed_price = 0;
+    bid.presale = presale;
+    bid.status = BidStatus::Pending;
     bid.bump = context.bumps.bid;
 
+    emit!(BidPlaced {
+        event: bid.event,
+        bidder: bid.bidder,
+        bid: bid.key(),
+        tier,
+        amount,
+        quantity,
+        max_price,
+        presale,
+        status: bid.status,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(tier: u8)]
+pub struct CommitBidAccountConstraints<'info> {
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    /// The PDA that will hold escrowed funds for the event
+    /// Seeds: [b"escrow", event.key().as_ref()]
+    #[account(mut, seeds = [b"escrow", event.key().as_ref()], bump)]
+    pub event_pda: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = bidder,
+        space = Bid::DISCRIMINATOR.len() + Bid::INIT_SPACE,
+        seeds = [b"bid", event.key().as_ref(), bidder.key().as_ref(), tier.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bid: Account<'info, Bid>,
+    /// Payment mint, required when the event is paid in tokens
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Bidder's token account for the payment mint
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = bidder,
+        token::token_program = token_program,
+    )]
+    pub bidder_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Escrow token vault owned by the event PDA
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = event_pda,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    /// Instructions sysvar, required when the event names an attestation issuer
+    /// CHECK: Address checked against the instructions sysvar ID
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn commit_bid(
+    context: Context<CommitBidAccountConstraints>,
+    tier: u8,
+    commitment: [u8; 32],
+    quantity: u32,
+    deposit: u64,
+) -> Result<()> {
+    let event = &mut context.accounts.event;
+    let bid = &mut context.accounts.bid;
+    let bidder = &context.accounts.bidder;
+    let event_pda = &context.accounts.event_pda;
+
+    // Get current time
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    // Check auction status
+    if event.status != EventStatus::Active {
+        return Err(error!(ErrorCode::AuctionNotActive));
+    }
+    if !event.is_sealed_bid() {
+        return Err(error!(ErrorCode::AuctionTypeMismatch));
+    }
+    if now < event.auction_start_time {
+        return Err(error!(ErrorCode::AuctionNotStarted));
+    }
+    if now > event.auction_end_time {
+        return Err(error!(ErrorCode::AuctionEnded));
+    }
+
+    // Events with an attestation issuer only take bids it has signed off on, checked before any funds move
+    if let Some(issuer) = event.attestation_issuer {
+        let instructions_sysvar = context
+            .accounts
+            .instructions_sysvar
+            .as_ref()
+            .ok_or(error!(ErrorCode::AttestationMissing))?;
+        verify_bid_attestation(instructions_sysvar, &issuer, &event.key(), &bidder.key(), now)?;
+    }
+
+    // One bid per wallet and tier, for up to the organizer's per-wallet limit
+    if !event.is_within_wallet_limit(quantity) {
+        return Err(error!(ErrorCode::WalletLimitExceeded));
+    }
+
+    // The deposit hides the price, but must at least cover every ticket at the tier's reserve price
+    let reserve = event.tier(tier)?.end_price.checked_mul(quantity as u64).ok_or(error!(ErrorCode::CustomError))?;
+    if deposit < reserve {
+        return Err(error!(ErrorCode::DepositTooLow));
+    }
+
+    // Escrow the deposit from bidder to event PDA (or its token vault)
+    transfer_payment(
+        event.payment_mint,
+        &bidder.to_account_info(),
+        &event_pda.to_account_info(),
+        TokenPaymentAccounts::from_optional(
+            &context.accounts.payment_mint,
+            &context.accounts.bidder_token_account,
+            &context.accounts.escrow_vault,
+            &context.accounts.token_program,
+        ),
+        deposit,
+        &context.accounts.system_program.to_account_info(),
+        None,
+    )?;
+    event.total_escrowed = event.total_escrowed.checked_add(deposit).ok_or(error!(ErrorCode::CustomError))?;
+
+    // Record the bid, its price stays hidden until revealed
+    bid.bidder = bidder.key();
+    bid.event = event.key();
+    bid.tier = tier;
+    bid.amount = deposit;
+    bid.quantity = quantity;
+    bid.tickets_awarded = 0;
+    bid.refunded_amount = 0;
+    bid.settled = false;
+    bid.commitment = commitment;
+    bid.revealed_price = 0;
+    bid.presale = false;
+    bid.status = BidStatus::Pending;
+    bid.bump = context.bumps.bid;
+
+    emit!(BidCommitted {
+        event: bid.event,
+        bidder: bid.bidder,
+        bid: bid.key(),
+        tier,
+        deposit,
+        quantity,
+        commitment,
+        status: bid.status,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevealBidAccountConstraints<'info> {
+    pub bidder: Signer<'info>,
+    #[account(
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [b"bid", bid.event.as_ref(), bid.bidder.as_ref(), bid.tier.to_le_bytes().as_ref()],
+        bump = bid.bump,
+        has_one = event @ ErrorCode::BidNotForEvent,
+        has_one = bidder @ ErrorCode::BidderMismatch,
+    )]
+    pub bid: Account<'info, Bid>,
+    /// Ranking of the event's revealed bids, which this bid joins
+    #[account(
+        mut,
+        seeds = [b"bid_ranking", event.key().as_ref()],
+        bump = bid_ranking.bump,
+    )]
+    pub bid_ranking: Account<'info, BidRanking>,
+}
+
+pub fn reveal_bid(
+    context: Context<RevealBidAccountConstraints>,
+    price: u64,
+    salt: [u8; 32],
+) -> Result<()> {
+    let event = &context.accounts.event;
+    let bid = &mut context.accounts.bid;
+
+    // Get current time
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    if event.status != EventStatus::Active {
+        return Err(error!(ErrorCode::AuctionNotActive));
+    }
+    if !event.is_sealed_bid() {
+        return Err(error!(ErrorCode::AuctionTypeMismatch));
+    }
+    if !event.is_reveal_open(now) {
+        return Err(error!(ErrorCode::RevealNotOpen));
+    }
+
+    if sealed_bid_commitment(price, &salt, &bid.bidder) != bid.commitment {
+        return Err(error!(ErrorCode::CommitmentMismatch));
+    }
+    if price < event.tier(bid.tier)?.end_price {
+        return Err(error!(ErrorCode::BidBelowReservePrice));
+    }
+    let total = price.checked_mul(bid.quantity as u64).ok_or(error!(ErrorCode::CustomError))?;
+    if total > bid.amount {
+        return Err(error!(ErrorCode::DepositTooLow));
+    }
+
+    // A bid that is never revealed stays Pending, and its whole deposit can be refunded
+    bid.revealed_price = price;
+    transition(&mut bid.status, BidStatus::Revealed)?;
+    let ticket_supply = event.tier(bid.tier)?.ticket_supply;
+    context.accounts.bid_ranking.tier_mut(bid.tier)?.record_revealed_bid(price, bid.quantity, ticket_supply);
+
+    emit!(BidRevealed {
+        event: bid.event,
+        bidder: bid.bidder,
+        bid: bid.key(),
+        price,
+        quantity: bid.quantity,
+        status: bid.status,
+        timestamp: now,
+    });
+
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(tier: u8)]
 pub struct AwardTicketAccountConstraints<'info> {
     #[account(mut)]
     pub organizer: Signer<'info>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
+        has_one = merkle_tree @ ErrorCode::MerkleTreeMismatch,
+    )]
     pub event: Account<'info, Event>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"bid", bid.event.as_ref(), bid.bidder.as_ref(), bid.tier.to_le_bytes().as_ref()],
+        bump = bid.bump,
+        has_one = event @ ErrorCode::BidNotForEvent,
+        has_one = bidder @ ErrorCode::BidderMismatch,
+        constraint = bid.tier == tier @ ErrorCode::BidNotForTier,
+    )]
     pub bid: Account<'info, Bid>,
+    /// The winning bidder, new leaf owner of the cNFT
+    /// CHECK: Checked against the bid
+    pub bidder: UncheckedAccount<'info>,
     #[account(
         init,
         payer = organizer,
         space = Ticket::DISCRIMINATOR.len() + Ticket::INIT_SPACE,
-        seeds = [b"ticket", event.key().as_ref(), bid.bidder.as_ref()],
+        seeds = [b"ticket", event.key().as_ref(), bid.bidder.as_ref(), bid.tier.to_le_bytes().as_ref(), bid.tickets_awarded.to_le_bytes().as_ref()],
         bump
     )]
     pub ticket: Account<'info, Ticket>,
+    /// The inventory page holding the ticket's cNFT
+    #[account(
+        mut,
+        seeds = [b"inventory", inventory.event.as_ref(), inventory.page.to_le_bytes().as_ref()],
+        bump = inventory.bump,
+        has_one = event @ ErrorCode::InventoryNotForEvent,
+    )]
+    pub inventory: Box<Account<'info, TicketInventory>>,
+    /// Ranking of the event's revealed bids, required for a sealed-bid auction
+    #[account(
+        mut,
+        seeds = [b"bid_ranking", event.key().as_ref()],
+        bump = bid_ranking.bump,
+    )]
+    pub bid_ranking: Option<Box<Account<'info, BidRanking>>>,
     /// Bubblegum Merkle Tree for cNFTs
-    /// CHECK: Verified in Bubblegum program CPI call
+    /// CHECK: Checked against the event, and verified in Bubblegum program CPI call
     #[account(mut)]
     pub merkle_tree: UncheckedAccount<'info>,
+    /// Bubblegum tree config PDA for the Merkle Tree
+    /// CHECK: Verified in Bubblegum program CPI call
+    pub tree_config: UncheckedAccount<'info>,
     /// Bubblegum program
     /// CHECK: Program ID verified in CPI
     pub bubblegum_program: UncheckedAccount<'info>,
@@ -120,157 +468,329 @@ pub struct AwardTicketAccountConstraints<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn award_ticket(
-    context: Context<AwardTicketAccountConstraints>,
+/// Remaining accounts: the Merkle proof path for `cnft_asset_id` (read-only), when the `bubblegum` feature is enabled.
+pub fn award_ticket<'info>(
+    context: Context<'_, '_, 'info, 'info, AwardTicketAccountConstraints<'info>>,
+    tier: u8,
     cnft_asset_id: Pubkey, // Asset ID to transfer
+    transfer_args: CnftTransferArgs,
 ) -> Result<()> {
     let event = &mut context.accounts.event;
     let bid = &mut context.accounts.bid;
-    let organizer = &context.accounts.organizer;
     let ticket = &mut context.accounts.ticket;
 
-    // Only the organizer can award tickets
-    if event.organizer != organizer.key() {
-        return Err(error!(ErrorCode::CustomError)); // Replace with specific error if desired
-    }
-    
     // Check event and bid status
-    if event.status != EVENT_STATUS_ACTIVE {
+    if event.status != EventStatus::Active {
         return Err(error!(ErrorCode::AuctionNotActive));
     }
-    
-    // Use the helper method to check if bid can be awarded
+    // Sealed bids can only win once revealed, and once every bid has had the chance to reveal
+    if event.is_sealed_bid()
+        && (bid.revealed_price == 0 || Clock::get()?.unix_timestamp <= event.auction_close_time())
+    {
+        return Err(error!(ErrorCode::BidNotRevealed));
+    }
+    // Only the top revealed bids win, whatever order the organizer awards them in
+    if event.is_sealed_bid() {
+        let bid_ranking = context.accounts.bid_ranking.as_mut().ok_or(error!(ErrorCode::BidRankingRequired))?;
+        let ticket_supply = event.tier(tier)?.ticket_supply;
+        let tier_ranking = bid_ranking.tier_mut(tier)?;
+        if !tier_ranking.can_award(bid.revealed_price, ticket_supply) {
+            return Err(error!(ErrorCode::BidOutranked));
+        }
+        tier_ranking.record_award(bid.revealed_price, ticket_supply).ok_or(error!(ErrorCode::CustomError))?;
+    }
     if !bid.can_award() {
-        return Err(error!(ErrorCode::CustomError)); // Replace with BidNotPending if desired
+        return Err(error!(ErrorCode::BidFullyAwarded));
     }
     
-    // Check if tickets are still available
-    if event.tickets_awarded >= event.ticket_supply {
-        return Err(error!(ErrorCode::CustomError)); // Replace with TicketsSoldOut if desired
+    // Check if the tier still has tickets available
+    if event.tier(tier)?.is_sold_out() {
+        return Err(error!(ErrorCode::TicketsSoldOut));
     }
 
-    // We'll use these in both branches
-    let _event_pda_seeds: &[&[u8]] = &[b"event", event.organizer.as_ref(), &[event.bump]];
+    // The asset must be one of this tier's cNFTs and must not have been awarded already
+    let asset_index = context.accounts.inventory.find_unawarded_asset(&cnft_asset_id, &event.tier_tickets(tier)?)?;
 
     // Bubblegum CPI: Transfer cNFT from event PDA to winner
     #[cfg(feature = "bubblegum")]
     {
-        // This code will be enabled when we properly integrate Bubblegum
-        // let transfer_ix = bubblegum_instruction::transfer_v2(
-        //     context.accounts.bubblegum_program.key(),
-        //     context.accounts.merkle_tree.key(),
-        //     event.key(), // event PDA as current owner
-        //     bid.bidder,  // new owner (winner)
-        //     cnft_asset_id,
-        //     event.key(), // event PDA as authority
-        //     None, // leaf delegate (optional)
-        //     None, // collection (optional)
-        // );
-        //
-        // anchor_lang::solana_program::program::invoke_signed(
-        //     &transfer_ix,
-        //     &[
-        //         context.accounts.bubblegum_program.to_account_info(),
-        //         context.accounts.merkle_tree.to_account_info(),
-        //         event.to_account_info(),
-        //         context.accounts.log_wrapper.to_account_info(),
-        //         context.accounts.compression_program.to_account_info(),
-        //         context.accounts.noop_program.to_account_info(),
-        //         context.accounts.system_program.to_account_info(),
-        //     ],
-        //     &[_event_pda_seeds],
-        // ).map_err(|_| error!(ErrorCode::CustomError))?;
+        let event_id_bytes = event.event_id.to_le_bytes();
+        let bump = [event.bump];
+        let event_pda_seeds: &[&[u8]] = &[b"event", event.organizer.as_ref(), &event_id_bytes, &bump];
+
+        transfer_cnft(
+            &context.accounts.bubblegum_program.to_account_info(),
+            &context.accounts.tree_config.to_account_info(),
+            &context.accounts.organizer.to_account_info(),
+            &event.to_account_info(), // event PDA as current owner
+            &context.accounts.bidder.to_account_info(), // new owner (winner)
+            &context.accounts.merkle_tree.to_account_info(),
+            &context.accounts.log_wrapper.to_account_info(),
+            &context.accounts.compression_program.to_account_info(),
+            &context.accounts.system_program.to_account_info(),
+            context.remaining_accounts,
+            &transfer_args,
+            &cnft_asset_id,
+            Some(event_pda_seeds),
+        )?;
     }
 
     // When bubblegum feature is not enabled, we just simulate the transfer
     #[cfg(not(feature = "bubblegum"))]
-    msg!("Bubblegum feature not enabled - simulating cNFT transfer for asset ID: {}", cnft_asset_id);
+    msg!(
+        "Bubblegum feature not enabled - simulating cNFT transfer for asset ID: {} (leaf {})",
+        cnft_asset_id,
+        transfer_args.index
+    );
 
-    // Mark bid as awarded
-    bid.status = BID_STATUS_AWARDED;
-    event.tickets_awarded = event.tickets_awarded.checked_add(1).ok_or(error!(ErrorCode::CustomError))?;
+    context.accounts.inventory.awarded[asset_index] = true;
+
+    // Mark bid as awarded, one ticket at a time
+    let unit_price = bid.unit_price().ok_or(error!(ErrorCode::CustomError))?;
+    let ticket_index = bid.tickets_awarded;
+    bid.tickets_awarded = bid.tickets_awarded.checked_add(1).ok_or(error!(ErrorCode::CustomError))?;
+    transition(&mut bid.status, BidStatus::Awarded)?;
+    // Presale tickets keep their fixed price, only public bids set the tier's clearing price
+    if bid.presale {
+        event.tier_mut(tier)?.record_presale_award(unit_price).ok_or(error!(ErrorCode::CustomError))?;
+    } else {
+        event.tier_mut(tier)?.record_awarded_bid(unit_price);
+    }
+    event.record_awarded_ticket(tier)?;
 
     // Create ticket
     ticket.owner = bid.bidder;
     ticket.event = event.key();
-    ticket.status = TICKET_STATUS_OWNED;
+    ticket.status = TicketStatus::Owned;
     ticket.offchain_ref = String::new(); // To be set by user later
     ticket.bump = context.bumps.ticket;
     ticket.cnft_asset_id = cnft_asset_id;
+    ticket.index = ticket_index;
+    ticket.tier = tier;
+    ticket.rent_payer = context.accounts.organizer.key();
+    ticket.purchase_price = 0;
+
+    emit!(TicketAwarded {
+        event: event.key(),
+        bidder: bid.bidder,
+        bid: bid.key(),
+        ticket: ticket.key(),
+        tier,
+        cnft_asset_id,
+        amount: unit_price,
+        tickets_awarded: event.tickets_awarded,
+        status: bid.status,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
 
     Ok(())
 }
 
 #[derive(Accounts)]
+#[instruction(tier: u8)]
 pub struct RefundBidAccountConstraints<'info> {
     #[account(mut)]
     pub bidder: Signer<'info>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+    )]
     pub event: Account<'info, Event>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"bid", bid.event.as_ref(), bid.bidder.as_ref(), bid.tier.to_le_bytes().as_ref()],
+        bump = bid.bump,
+        has_one = event @ ErrorCode::BidNotForEvent,
+        has_one = bidder @ ErrorCode::BidderMismatch,
+        constraint = bid.tier == tier @ ErrorCode::BidNotForTier,
+    )]
     pub bid: Account<'info, Bid>,
     /// Event PDA (escrow authority)
     #[account(mut, seeds = [b"escrow", event.key().as_ref()], bump)]
     pub event_pda: SystemAccount<'info>,
+    /// Payment mint, required when the event is paid in tokens
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Bidder's token account for the payment mint
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = bidder,
+        token::token_program = token_program,
+    )]
+    pub bidder_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Escrow token vault owned by the event PDA
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = event_pda,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn refund_bid(
-    context: Context<RefundBidAccountConstraints>,
+/// Remaining accounts: for an awarded bid of a cancelled event, every ticket awarded to the bid (writable), in index order.
+pub fn refund_bid<'info>(
+    context: Context<'_, '_, 'info, 'info, RefundBidAccountConstraints<'info>>,
+    tier: u8,
 ) -> Result<()> {
     let event = &mut context.accounts.event;
     let bid = &mut context.accounts.bid;
     let bidder = &context.accounts.bidder;
     let event_pda = &context.accounts.event_pda;
 
+    // Get current time
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
     // Use the helper method to check if bid can be refunded
     if !bid.can_refund() {
-        return Err(error!(ErrorCode::CustomError)); // Already refunded
+        return Err(error!(ErrorCode::BidAlreadyRefunded));
     }
 
-    let refund_amount;
-    if bid.status == BID_STATUS_PENDING {
-        // Case 1: Bid did not win, full refund
-        refund_amount = bid.amount;
-        bid.status = BID_STATUS_REFUNDED;
-    } else if bid.status == BID_STATUS_AWARDED {
-        // Case 2: Bid won, partial refund if closing price < bid amount
-        // We need the auction to be finalized to know the closing price
-        if event.status != EVENT_STATUS_FINALIZED || event.auction_close_price == 0 {
-            return Err(error!(ErrorCode::CustomError)); // Auction not finalized, can't refund
+    // Total the bid is owed back for the event's outcome, and whether that outcome is final
+    let close_price = event.tier(tier)?.close_price;
+    let refund_due;
+    let settles;
+    if event.status == EventStatus::Cancelled {
+        // Event cancelled: every bid gets its full escrowed amount back, and awarded tickets are voided
+        if context.remaining_accounts.len() != bid.tickets_awarded as usize {
+            return Err(error!(ErrorCode::TicketAccountRequired));
         }
-        
-        let close_price = event.auction_close_price;
-        if bid.amount > close_price {
-            refund_amount = bid.amount - close_price;
-        } else {
-            // No refund needed
-            return Ok(());
+        let event_key = event.key();
+        for (index, ticket_info) in context.remaining_accounts.iter().enumerate() {
+            let mut ticket = Account::<Ticket>::try_from(ticket_info)?;
+            let index_bytes = (index as u32).to_le_bytes();
+            let expected_ticket = Pubkey::create_program_address(
+                &[b"ticket", event_key.as_ref(), bid.bidder.as_ref(), &[tier], &index_bytes, &[ticket.bump]],
+                context.program_id,
+            )
+            .map_err(|_| error!(ErrorCode::TicketAccountRequired))?;
+            if ticket_info.key() != expected_ticket {
+                return Err(error!(ErrorCode::TicketAccountRequired));
+            }
+            if !ticket.can_refund() {
+                return Err(error!(ErrorCode::TicketNotRefundable));
+            }
+            transition(&mut ticket.status, TicketStatus::Refunded)?;
+            ticket.exit(context.program_id)?;
         }
-        // Do not mark as refunded, as the ticket is already awarded
+        refund_due = bid.amount;
+        settles = true;
+        transition(&mut bid.status, BidStatus::Refunded)?;
+    } else if bid.status == BidStatus::Pending {
+        // Case 1: Bid did not win, or a sealed bid was never revealed: full refund
+        refund_due = bid.amount;
+        settles = true;
+        transition(&mut bid.status, BidStatus::Refunded)?;
     } else {
-        return Err(error!(ErrorCode::CustomError)); // Invalid bid status
+        // Case 2: Bid won some or all of its tickets, refund what the awarded tickets did not cost at the tier's closing price
+        // We need the auction to be finalized to know the closing price
+        if event.status != EventStatus::Finalized {
+            return Err(error!(ErrorCode::EventNotFinalized));
+        }
+        refund_due = bid.refund_due(close_price).ok_or(error!(ErrorCode::CustomError))?;
+        // The bid stays open while the event can still be cancelled, which would make all of it refundable
+        settles = !event.can_cancel(now);
+    }
+
+    // Only pay what has not been refunded already
+    let refund_amount = bid.refund_outstanding(refund_due).ok_or(error!(ErrorCode::CustomError))?;
+    if refund_amount == 0 && !settles {
+        // Nothing new to pay or settle
+        return Ok(());
     }
 
     if refund_amount > 0 {
         let event_key = event.key();
         let bump = &[context.bumps.event_pda];
         let event_pda_seeds: &[&[u8]] = &[b"escrow", event_key.as_ref(), bump];
-        let ix = anchor_lang::solana_program::system_instruction::transfer(
-            &event_pda.key(),
-            &bidder.key(),
+        transfer_payment(
+            event.payment_mint,
+            &event_pda.to_account_info(),
+            &bidder.to_account_info(),
+            TokenPaymentAccounts::from_optional(
+                &context.accounts.payment_mint,
+                &context.accounts.escrow_vault,
+                &context.accounts.bidder_token_account,
+                &context.accounts.token_program,
+            ),
             refund_amount,
-        );
-        anchor_lang::solana_program::program::invoke_signed(
-            &ix,
-            &[
-                event_pda.to_account_info(),
-                bidder.to_account_info(),
-                context.accounts.system_program.to_account_info(),
-            ],
-            &[event_pda_seeds],
-        ).map_err(|_| error!(ErrorCode::CustomError))?;
+            &context.accounts.system_program.to_account_info(),
+            Some(event_pda_seeds),
+        )?;
+        bid.refunded_amount = bid.refunded_amount.checked_add(refund_amount).ok_or(error!(ErrorCode::CustomError))?;
+        event.total_refunded = event.total_refunded.checked_add(refund_amount).ok_or(error!(ErrorCode::CustomError))?;
     }
+    bid.settled = settles;
+
+    emit!(BidRefunded {
+        event: event.key(),
+        bidder: bid.bidder,
+        bid: bid.key(),
+        refund_amount,
+        refunded_amount: bid.refunded_amount,
+        settled: bid.settled,
+        close_price,
+        status: bid.status,
+        timestamp: now,
+    });
+
+    // A fully settled bid has nothing left to track, so its rent goes back to the bidder
+    if bid.settled {
+        bid.close(bidder.to_account_info())?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseBidAccountConstraints<'info> {
+    /// The bidder, who paid the bid's rent
+    #[account(mut)]
+    pub bidder: Signer<'info>,
+    #[account(
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        close = bidder,
+        seeds = [b"bid", bid.event.as_ref(), bid.bidder.as_ref(), bid.tier.to_le_bytes().as_ref()],
+        bump = bid.bump,
+        has_one = event @ ErrorCode::BidNotForEvent,
+        has_one = bidder @ ErrorCode::BidderMismatch,
+    )]
+    pub bid: Account<'info, Bid>,
+}
+
+pub fn close_bid(
+    context: Context<CloseBidAccountConstraints>,
+) -> Result<()> {
+    let event = &context.accounts.event;
+    let bid = &context.accounts.bid;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Settled bids are already closed by refund_bid, so this covers a won bid with nothing above the tier's close price
+    // to refund: escrow stays in the event PDA, so the bid can only go once the auction is final and cannot be cancelled
+    let closable = event.status == EventStatus::Finalized && !event.can_cancel(now) && {
+        let refund_due = bid.refund_due(event.tier(bid.tier)?.close_price).ok_or(error!(ErrorCode::CustomError))?;
+        bid.refund_outstanding(refund_due) == Some(0)
+    };
+    if !closable {
+        return Err(error!(ErrorCode::BidNotClosable));
+    }
+
+    emit!(BidClosed {
+        event: event.key(),
+        bidder: bid.bidder,
+        bid: bid.key(),
+        timestamp: now,
+    });
 
     Ok(())
-}
\ No newline at end of file
+}
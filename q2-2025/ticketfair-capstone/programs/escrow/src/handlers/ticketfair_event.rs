@@ -1,32 +1,77 @@
 //! Ticketfair event instruction handlers
 
 use anchor_lang::prelude::*;
-use crate::state::Event;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use crate::state::{transition, AuctionType, BidRanking, Event, EventStatus, ProtocolConfig, TicketInventory, TicketTierArgs, TierRanking, User, BPS_DENOMINATOR};
 use crate::constants::*;
 use crate::error::ErrorCode;
+use crate::events::{AuctionFinalized, AuctionTypeSet, EventActivated, EventCancelled, EventCreated, AttestationIssuerSet, GateAuthoritySet, PresaleSet, ProceedsWithdrawn, ResaleTermsSet, TicketsMinted};
+use crate::handlers::shared::{escrow_rent_reserve, is_supported_payment_mint, transfer_lamports, transfer_payment, TokenPaymentAccounts};
+#[cfg(feature = "bubblegum")]
+use crate::handlers::shared::{is_tree_delegate, merkle_tree_account_size, merkle_tree_dimensions};
 
-// We'll add these imports back when we properly integrate Bubblegum
-// #[cfg(feature = "bubblegum")]
-// use mpl_bubblegum::instruction as bubblegum_instruction;
-// #[cfg(feature = "bubblegum")]
-// use mpl_bubblegum::state::metaplex_adapter::MetadataArgsV2;
+#[cfg(feature = "bubblegum")]
+use mpl_bubblegum::{
+    accounts::TreeConfig,
+    instructions::{
+        CreateTreeConfigV2Cpi, CreateTreeConfigV2CpiAccounts, CreateTreeConfigV2InstructionArgs,
+        MintV2Cpi, MintV2CpiAccounts, MintV2InstructionArgs,
+    },
+    types::{MetadataArgsV2, TokenStandard},
+    utils::get_asset_id,
+};
 
 #[derive(Accounts)]
 pub struct CreateEventAccountConstraints<'info> {
     #[account(mut)]
     pub organizer: Signer<'info>,
+    /// The organizer's user account, whose `events_created` counter numbers the event
+    #[account(
+        mut,
+        seeds = [b"user", organizer.key().as_ref()],
+        bump = user.bump,
+    )]
+    pub user: Account<'info, User>,
     #[account(
         init,
         payer = organizer,
         space = 8 + Event::INIT_SPACE, // 8 bytes for discriminator
-        seeds = [b"event", organizer.key().as_ref()],
+        seeds = [b"event", organizer.key().as_ref(), user.events_created.to_le_bytes().as_ref()],
         bump
     )]
     pub event: Account<'info, Event>,
-    /// Bubblegum Merkle Tree for cNFTs (must be created before event)
-    /// CHECK: Verified in Bubblegum program CPI call
+    /// Protocol config, whose limits the event is checked against and whose fee it keeps
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// Event PDA (escrow authority) that owns the payment vault, or holds the escrowed lamports
+    #[account(mut, seeds = [b"escrow", event.key().as_ref()], bump)]
+    pub event_pda: SystemAccount<'info>,
+    /// Optional SPL Token / Token-2022 mint bids are paid in (native SOL when omitted)
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Escrow token vault owned by the event PDA, created when a payment mint is given
+    #[account(
+        init,
+        payer = organizer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = event_pda,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    /// Bubblegum Merkle Tree for cNFTs, allocated by the organizer for the compression program and sized for the ticket supply.
+    /// Trees for large supplies outgrow what an account created inside a CPI may hold, so it is not created here.
+    /// CHECK: Owner and size verified in handler, initialized in Bubblegum program CPI call
     #[account(mut)]
     pub merkle_tree: UncheckedAccount<'info>,
+    /// Bubblegum tree config PDA for the Merkle Tree, created with the event PDA as tree creator
+    /// CHECK: Address verified in handler, initialized in Bubblegum program CPI call
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
     /// Bubblegum program
     /// CHECK: Program ID verified in CPI
     pub bubblegum_program: UncheckedAccount<'info>,
@@ -45,101 +90,340 @@ pub struct CreateEventAccountConstraints<'info> {
 pub fn create_event(
     context: Context<CreateEventAccountConstraints>,
     metadata_url: String,
-    ticket_supply: u32,
-    start_price: u64,
-    end_price: u64,
+    tiers: Vec<TicketTierArgs>,
+    max_tickets_per_wallet: u32,
     auction_start_time: i64,
     auction_end_time: i64,
+    event_start_time: i64,
 ) -> Result<()> {
     let event = &mut context.accounts.event;
+    let protocol_config = &context.accounts.protocol_config;
+    let limits = &protocol_config.limits;
     
     // Validate parameters
-    if ticket_supply > MAX_TICKETS_PER_EVENT {
-        return Err(error!(ErrorCode::CustomError)); // Replace with specific error
+    if tiers.is_empty() || tiers.len() > MAX_TIERS_PER_EVENT {
+        return Err(error!(ErrorCode::InvalidTicketTiers));
     }
-    
-    if start_price < MIN_TICKET_PRICE || end_price < MIN_TICKET_PRICE {
-        return Err(error!(ErrorCode::CustomError)); // Replace with specific error
+
+    for tier in &tiers {
+        if tier.ticket_supply == 0 || tier.metadata_url.len() > MAX_METADATA_URL_LEN {
+            return Err(error!(ErrorCode::InvalidTicketTiers));
+        }
+
+        limits.check_ticket_price(tier.start_price)?;
+        limits.check_ticket_price(tier.end_price)?;
+
+        if !tier.price_curve.is_valid() {
+            return Err(error!(ErrorCode::InvalidPriceCurve));
+        }
+    }
+
+    // The Merkle tree is sized for every tier's tickets together
+    let ticket_supply = tiers
+        .iter()
+        .try_fold(0u32, |total, tier| total.checked_add(tier.ticket_supply))
+        .ok_or(error!(ErrorCode::TicketSupplyAboveMaximum))?;
+    limits.check_ticket_supply(ticket_supply)?;
+
+    // The limit applies in each tier, so it is only capped by the largest one
+    let largest_tier = tiers.iter().map(|tier| tier.ticket_supply).max().unwrap_or(0);
+    if max_tickets_per_wallet == 0 || max_tickets_per_wallet > largest_tier {
+        return Err(error!(ErrorCode::InvalidWalletLimit));
     }
     
-    let duration = auction_end_time - auction_start_time;
-    if duration < MIN_AUCTION_DURATION || duration > MAX_AUCTION_DURATION {
-        return Err(error!(ErrorCode::CustomError)); // Replace with specific error
+    limits.check_auction(auction_start_time, auction_end_time)?;
+
+    if event_start_time < auction_end_time {
+        return Err(error!(ErrorCode::InvalidEventStartTime));
+    }
+
+    // A payment mint needs its escrow vault, and must not take a fee out of what reaches it
+    if let Some(payment_mint) = &context.accounts.payment_mint {
+        if context.accounts.escrow_vault.is_none() {
+            return Err(error!(ErrorCode::PaymentAccountsRequired));
+        }
+        if !is_supported_payment_mint(&payment_mint.to_account_info().try_borrow_data()?) {
+            return Err(error!(ErrorCode::UnsupportedPaymentMint));
+        }
     }
     
+    let user = &mut context.accounts.user;
     event.organizer = context.accounts.organizer.key();
+    event.event_id = user.events_created;
     event.metadata_url = metadata_url.clone();
     event.ticket_supply = ticket_supply;
     event.tickets_awarded = 0;
-    event.start_price = start_price;
-    event.end_price = end_price;
+    event.max_tickets_per_wallet = max_tickets_per_wallet;
+    event.tiers = tiers.into_iter().map(Into::into).collect();
+    event.auction_type = AuctionType::Dutch;
+    event.presale_start_time = 0;
+    event.allowlist_root = [0; 32];
+    event.presale_discount_bps = 0;
+    event.attestation_issuer = None;
     event.auction_start_time = auction_start_time;
     event.auction_end_time = auction_end_time;
-    event.auction_close_price = 0;
-    event.status = EVENT_STATUS_CREATED;
+    event.event_start_time = event_start_time;
+    event.gate_authority = context.accounts.organizer.key();
+    event.check_in_opens_before = DEFAULT_CHECK_IN_OPENS_BEFORE;
+    event.check_in_closes_after = DEFAULT_CHECK_IN_CLOSES_AFTER;
+    event.resale_cap_bps = DEFAULT_RESALE_CAP_BPS;
+    event.resale_royalty_bps = DEFAULT_RESALE_ROYALTY_BPS;
+    event.protocol_fee_bps = protocol_config.fee_bps;
+    event.status = EventStatus::Created;
     event.bump = context.bumps.event;
+    user.events_created = user.events_created.checked_add(1).ok_or(error!(ErrorCode::CustomError))?;
+    event.payment_mint = context.accounts.payment_mint.as_ref().map(|mint| mint.key());
     event.merkle_tree = context.accounts.merkle_tree.key();
-    let ticket_supply_to_reserve = ticket_supply as usize;
-    event.cnft_asset_ids = Vec::with_capacity(ticket_supply_to_reserve);
+    // Tickets are minted into inventory pages afterwards, with `mint_tickets`
+    event.tickets_minted = 0;
 
-    // Bubblegum CPI: Mint cNFTs for ticket supply
+    // Fund a SOL escrow up front, so no payment out of it can ever leave it below rent exemption
+    let rent_reserve = escrow_rent_reserve(event.payment_mint)?;
+    if rent_reserve > 0 {
+        transfer_lamports(
+            &context.accounts.organizer.to_account_info(),
+            &context.accounts.event_pda.to_account_info(),
+            rent_reserve,
+            &context.accounts.system_program.to_account_info(),
+            None,
+        )?;
+    }
+
+    // Bubblegum CPI: Create the Merkle Tree, with a leaf for every ticket
     #[cfg(feature = "bubblegum")]
     {
-        // This code will be enabled when we properly integrate Bubblegum
-        // for i in 0..ticket_supply {
-        //     let metadata = MetadataArgsV2 {
-        //         name: format!("Ticket #{}", i + 1),
-        //         uri: metadata_url.clone(),
-        //         seller_fee_basis_points: 0,
-        //         collection: None,
-        //         creators: vec![],
-        //         // Add other fields as required by Bubblegum v2
-        //     };
-        //
-        //     let mint_ix = bubblegum_instruction::mint_v2(
-        //         context.accounts.bubblegum_program.key(),
-        //         context.accounts.merkle_tree.key(),
-        //         event.key(), // event PDA as tree delegate/authority
-        //         event.key(), // leaf owner (event PDA)
-        //         event.key(), // leaf delegate (event PDA)
-        //         None, // collection authority (optional)
-        //         None, // core collection (optional)
-        //         metadata,
-        //     );
-        //
-        //     let event_pda_seeds: &[&[u8]] = &[b"event", event.organizer.as_ref(), &[event.bump]];
-        //
-        //     anchor_lang::solana_program::program::invoke_signed(
-        //         &mint_ix,
-        //         &[
-        //             context.accounts.bubblegum_program.to_account_info(),
-        //             context.accounts.merkle_tree.to_account_info(),
-        //             event.to_account_info(),
-        //             context.accounts.log_wrapper.to_account_info(),
-        //             context.accounts.compression_program.to_account_info(),
-        //             context.accounts.noop_program.to_account_info(),
-        //             context.accounts.system_program.to_account_info(),
-        //         ],
-        //         &[event_pda_seeds],
-        //     ).map_err(|_| error!(crate::error::ErrorCode::CustomError))?;
-        //
-        //     // TODO: Parse asset ID from transaction logs off-chain and update event.cnft_asset_ids
-        //     // For now, push a placeholder
-        //     event.cnft_asset_ids.push(Pubkey::default());
-        // }
+        let bubblegum_program = context.accounts.bubblegum_program.to_account_info();
+        if bubblegum_program.key() != mpl_bubblegum::ID {
+            return Err(error!(ErrorCode::InvalidBubblegumProgram));
+        }
+
+        let event_info = event.to_account_info();
+        let merkle_tree_info = context.accounts.merkle_tree.to_account_info();
+        let merkle_tree_key = merkle_tree_info.key();
+        let tree_config_info = context.accounts.tree_config.to_account_info();
+        if tree_config_info.key() != TreeConfig::find_pda(&merkle_tree_key).0 {
+            return Err(error!(ErrorCode::InvalidTreeConfig));
+        }
+
+        let organizer_key = context.accounts.organizer.key();
+        let event_id_bytes = event.event_id.to_le_bytes();
+        let bump = [event.bump];
+        let event_pda_seeds: &[&[u8]] = &[b"event", organizer_key.as_ref(), &event_id_bytes, &bump];
+
+        // The organizer allocates the tree, owned by the compression program, with a leaf for every ticket and no canopy
+        let (max_depth, max_buffer_size) = merkle_tree_dimensions(ticket_supply)
+            .ok_or(error!(ErrorCode::CustomError))?;
+        if merkle_tree_info.owner != context.accounts.compression_program.key
+            || merkle_tree_info.data_len() != merkle_tree_account_size(max_depth, max_buffer_size)
+        {
+            return Err(error!(ErrorCode::InvalidMerkleTreeAccount));
+        }
+
+        // The event PDA creates the tree, so it is also the tree delegate that mints tickets
+        CreateTreeConfigV2Cpi::new(
+            &bubblegum_program,
+            CreateTreeConfigV2CpiAccounts {
+                tree_config: &tree_config_info,
+                merkle_tree: &merkle_tree_info,
+                payer: &context.accounts.organizer.to_account_info(),
+                tree_creator: Some(&event_info),
+                log_wrapper: &context.accounts.log_wrapper.to_account_info(),
+                compression_program: &context.accounts.compression_program.to_account_info(),
+                system_program: &context.accounts.system_program.to_account_info(),
+            },
+            CreateTreeConfigV2InstructionArgs {
+                max_depth,
+                max_buffer_size,
+                public: Some(false),
+            },
+        )
+        .invoke_signed(&[event_pda_seeds])?;
     }
-    
+
+    #[cfg(not(feature = "bubblegum"))]
+    msg!("Bubblegum feature not enabled - simulating Merkle tree creation for {} tickets", ticket_supply);
+
+    emit!(EventCreated {
+        event: event.key(),
+        organizer: event.organizer,
+        event_id: event.event_id,
+        ticket_supply,
+        max_tickets_per_wallet,
+        tiers: event.tiers.clone(),
+        auction_start_time,
+        auction_end_time,
+        payment_mint: event.payment_mint,
+        status: event.status,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MintTicketsAccountConstraints<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
+        has_one = merkle_tree @ ErrorCode::MerkleTreeMismatch,
+    )]
+    pub event: Account<'info, Event>,
+    /// The inventory page the next tickets go onto, created when the previous page is full
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = TicketInventory::DISCRIMINATOR.len() + TicketInventory::INIT_SPACE,
+        seeds = [b"inventory", event.key().as_ref(), event.next_inventory_page().to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub inventory: Box<Account<'info, TicketInventory>>,
+    /// Bubblegum Merkle Tree for cNFTs
+    /// CHECK: Checked against the event, and verified in Bubblegum program CPI call
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// Bubblegum tree config PDA for the Merkle Tree (tree delegate must be the event PDA)
+    /// CHECK: Address, owner and delegate verified in handler when minting
+    #[account(mut)]
+    pub tree_config: UncheckedAccount<'info>,
+    /// Bubblegum program
+    /// CHECK: Program ID verified in CPI
+    pub bubblegum_program: UncheckedAccount<'info>,
+    /// Log wrapper program (required by Bubblegum)
+    /// CHECK: Program ID verified in CPI
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// Compression program (required by Bubblegum)
+    /// CHECK: Program ID verified in CPI
+    pub compression_program: UncheckedAccount<'info>,
+    /// MPL Core program (required by Bubblegum mint_v2)
+    /// CHECK: Program ID verified in CPI
+    pub mpl_core_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Mint `count` of the event's tickets onto its current inventory page
+pub fn mint_tickets(
+    context: Context<MintTicketsAccountConstraints>,
+    count: u32,
+) -> Result<()> {
+    let event = &mut context.accounts.event;
+    let inventory = &mut context.accounts.inventory;
+
+    // The inventory is fixed once the auction is activated
+    if event.status != EventStatus::Created {
+        return Err(error!(ErrorCode::InvalidEventStatusTransition));
+    }
+
+    // A new page, created by this call
+    if inventory.event == Pubkey::default() {
+        inventory.event = event.key();
+        inventory.page = event.next_inventory_page();
+        inventory.asset_ids = Vec::new();
+        inventory.awarded = Vec::new();
+        inventory.bump = context.bumps.inventory;
+    }
+
+    let tickets_left = event.ticket_supply.checked_sub(event.tickets_minted).ok_or(error!(ErrorCode::CustomError))?;
+    if count == 0 || count > tickets_left.min(inventory.remaining_capacity()) {
+        return Err(error!(ErrorCode::InvalidMintCount));
+    }
+
+    // Bubblegum CPI: Mint cNFTs onto the page
+    #[cfg(feature = "bubblegum")]
+    {
+        let bubblegum_program = context.accounts.bubblegum_program.to_account_info();
+        if bubblegum_program.key() != mpl_bubblegum::ID {
+            return Err(error!(ErrorCode::InvalidBubblegumProgram));
+        }
+
+        let merkle_tree_info = context.accounts.merkle_tree.to_account_info();
+        let merkle_tree_key = merkle_tree_info.key();
+        let tree_config_info = context.accounts.tree_config.to_account_info();
+        if tree_config_info.key() != TreeConfig::find_pda(&merkle_tree_key).0
+            || *tree_config_info.owner != mpl_bubblegum::ID
+        {
+            return Err(error!(ErrorCode::InvalidTreeConfig));
+        }
+
+        let event_id_bytes = event.event_id.to_le_bytes();
+        let bump = [event.bump];
+        let event_pda_seeds: &[&[u8]] = &[b"event", event.organizer.as_ref(), &event_id_bytes, &bump];
+        let event_info = event.to_account_info();
+
+        for i in event.tickets_minted..event.tickets_minted + count {
+            // Tickets are minted tier by tier, each with its tier's metadata
+            let tier = event.tier_of_ticket(i).ok_or(error!(ErrorCode::InvalidTier))?;
+            // The asset ID is derived from the leaf nonce, which is the tree's mint count before this mint
+            let tree_config = TreeConfig::try_from(&tree_config_info)
+                .map_err(|_| error!(ErrorCode::InvalidTreeConfig))?;
+            if !is_tree_delegate(&tree_config.tree_delegate, &event_info.key()) {
+                return Err(error!(ErrorCode::TreeDelegateMismatch));
+            }
+            let asset_id = get_asset_id(&merkle_tree_key, tree_config.num_minted);
+
+            let metadata = MetadataArgsV2 {
+                name: format!("Ticket #{}", i + 1),
+                symbol: String::from("TICKET"),
+                uri: event.tier(tier)?.metadata_url.clone(),
+                seller_fee_basis_points: 0,
+                primary_sale_happened: false,
+                is_mutable: true,
+                token_standard: Some(TokenStandard::NonFungible),
+                creators: vec![],
+                collection: None,
+            };
+
+            MintV2Cpi::new(
+                &bubblegum_program,
+                MintV2CpiAccounts {
+                    tree_config: &tree_config_info,
+                    payer: &context.accounts.organizer.to_account_info(),
+                    tree_creator_or_delegate: Some(&event_info), // event PDA as tree delegate
+                    collection_authority: None,
+                    leaf_owner: &event_info, // event PDA holds every ticket until awarded
+                    leaf_delegate: Some(&event_info),
+                    merkle_tree: &merkle_tree_info,
+                    core_collection: None,
+                    mpl_core_cpi_signer: None,
+                    log_wrapper: &context.accounts.log_wrapper.to_account_info(),
+                    compression_program: &context.accounts.compression_program.to_account_info(),
+                    mpl_core_program: &context.accounts.mpl_core_program.to_account_info(),
+                    system_program: &context.accounts.system_program.to_account_info(),
+                },
+                MintV2InstructionArgs {
+                    metadata,
+                    asset_data: None,
+                    asset_data_schema: None,
+                },
+            )
+            .invoke_signed(&[event_pda_seeds])?;
+
+            inventory.add_asset(asset_id)?;
+        }
+    }
+
     // When bubblegum feature is not enabled, we just simulate the minting
     #[cfg(not(feature = "bubblegum"))]
     {
-        msg!("Bubblegum feature not enabled - simulating cNFT minting for {} tickets", ticket_supply);
-        // Create placeholder asset IDs for testing
-        event.cnft_asset_ids = Vec::new();
-        for _ in 0..ticket_supply {
-            event.cnft_asset_ids.push(Pubkey::default());
+        msg!("Bubblegum feature not enabled - simulating cNFT minting for {} tickets", count);
+        // Placeholder asset IDs for testing
+        for _ in 0..count {
+            inventory.add_asset(Pubkey::default())?;
         }
     }
 
+    event.tickets_minted = event.tickets_minted.checked_add(count).ok_or(error!(ErrorCode::CustomError))?;
+
+    emit!(TicketsMinted {
+        event: event.key(),
+        inventory: inventory.key(),
+        page: inventory.page,
+        count,
+        tickets_minted: event.tickets_minted,
+    });
+
     Ok(())
 }
 
@@ -149,9 +433,9 @@ pub struct ActivateEventAccountConstraints<'info> {
     pub organizer: Signer<'info>,
     #[account(
         mut,
-        seeds = [b"event", organizer.key().as_ref()],
-        bump,
-        constraint = event.organizer == organizer.key() @ ErrorCode::CustomError,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
     )]
     pub event: Account<'info, Event>,
 }
@@ -160,14 +444,30 @@ pub fn activate_event(
     context: Context<ActivateEventAccountConstraints>,
 ) -> Result<()> {
     let event = &mut context.accounts.event;
-    
-    // Only activate if the event is in the Created state
-    if event.status != EVENT_STATUS_CREATED {
-        return Err(error!(ErrorCode::CustomError)); // Replace with specific error
+
+    // Get current time
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    // An event activated after its auction ended could never take a bid
+    if now > event.auction_end_time {
+        return Err(error!(ErrorCode::AuctionEnded));
+    }
+
+    // Every ticket must be in the inventory before bidding opens
+    if !event.is_inventory_complete() {
+        return Err(error!(ErrorCode::TicketInventoryIncomplete));
     }
     
-    // Set the event status to Active
-    event.status = EVENT_STATUS_ACTIVE;
+    // Only Created events can be activated
+    transition(&mut event.status, EventStatus::Active)?;
+
+    emit!(EventActivated {
+        event: event.key(),
+        organizer: event.organizer,
+        status: event.status,
+        timestamp: now,
+    });
     
     Ok(())
 }
@@ -178,16 +478,15 @@ pub struct FinalizeEventAccountConstraints<'info> {
     pub organizer: Signer<'info>,
     #[account(
         mut,
-        seeds = [b"event", organizer.key().as_ref()],
-        bump,
-        constraint = event.organizer == organizer.key() @ ErrorCode::CustomError,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
     )]
     pub event: Account<'info, Event>,
 }
 
 pub fn finalize_auction(
     context: Context<FinalizeEventAccountConstraints>,
-    close_price: u64,
 ) -> Result<()> {
     let event = &mut context.accounts.event;
     
@@ -195,21 +494,446 @@ pub fn finalize_auction(
     let clock = Clock::get()?;
     let now = clock.unix_timestamp;
     
-    // Check if the auction can be finalized
-    if !event.can_finalize(now) {
-        return Err(error!(ErrorCode::CustomError)); // Replace with specific error
+    // Only Active events can be finalized, and only once the auction (and any sealed bid reveals) has closed
+    if event.status == EventStatus::Active && !event.can_finalize(now) {
+        return Err(error!(ErrorCode::AuctionNotEnded));
     }
+    transition(&mut event.status, EventStatus::Finalized)?;
     
-    // Validate close price is between start and end prices
-    if close_price > event.start_price || close_price < event.end_price {
-        return Err(error!(ErrorCode::CustomError)); // Replace with specific error
+    // Set each tier's close price to its uniform clearing price (lowest awarded bid)
+    for tier in event.tiers.iter_mut() {
+        tier.close_price = tier.clearing_price();
     }
-    
-    // Set the auction close price
-    event.auction_close_price = close_price;
-    
-    // Update event status to finalized
-    event.status = EVENT_STATUS_FINALIZED;
+
+    emit!(AuctionFinalized {
+        event: event.key(),
+        close_prices: event.tiers.iter().map(|tier| tier.close_price).collect(),
+        tickets_awarded: event.tickets_awarded,
+        status: event.status,
+        timestamp: now,
+    });
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct CancelEventAccountConstraints<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
+    )]
+    pub event: Account<'info, Event>,
+}
+
+pub fn cancel_event(
+    context: Context<CancelEventAccountConstraints>,
+) -> Result<()> {
+    let event = &mut context.accounts.event;
+
+    // Get current time
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    // Created and Active events can always be cancelled, Finalized ones only until check-in opens
+    if !event.can_cancel(now) {
+        return Err(error!(ErrorCode::EventCannotBeCancelled));
+    }
+
+    // Every bid becomes fully refundable through refund_bid, and every fixed-price ticket through refund_ticket
+    transition(&mut event.status, EventStatus::Cancelled)?;
+
+    emit!(EventCancelled {
+        event: event.key(),
+        organizer: event.organizer,
+        status: event.status,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProceedsAccountConstraints<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
+    )]
+    pub event: Account<'info, Event>,
+    /// Event PDA (escrow authority)
+    #[account(mut, seeds = [b"escrow", event.key().as_ref()], bump)]
+    pub event_pda: SystemAccount<'info>,
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+    /// Protocol treasury, receives the platform fee
+    /// CHECK: Checked against the protocol config
+    #[account(mut, address = protocol_config.treasury @ ErrorCode::TreasuryMismatch)]
+    pub treasury: UncheckedAccount<'info>,
+    /// Payment mint, required when the event is paid in tokens
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Organizer's token account for the payment mint
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = organizer,
+        token::token_program = token_program,
+    )]
+    pub organizer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Treasury's token account for the payment mint, receives the platform fee
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = treasury,
+        token::token_program = token_program,
+    )]
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Escrow token vault owned by the event PDA
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = event_pda,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn withdraw_proceeds(
+    context: Context<WithdrawProceedsAccountConstraints>,
+) -> Result<()> {
+    let event = &mut context.accounts.event;
+    let event_pda = &context.accounts.event_pda;
+
+    // Proceeds are only known once the closing price is set
+    if event.status != EventStatus::Finalized {
+        return Err(error!(ErrorCode::EventNotFinalized));
+    }
+
+    // Pay out only what has not been withdrawn yet
+    let total_proceeds = event.total_proceeds().ok_or(error!(ErrorCode::CustomError))?;
+    let amount = total_proceeds.saturating_sub(event.proceeds_withdrawn);
+    if amount == 0 {
+        return Err(error!(ErrorCode::NoProceedsToWithdraw));
+    }
+
+    // Leave enough in escrow for every partial (winner) and full (loser) refund still owed, above its rent reserve
+    let escrow_balance = match event.payment_mint {
+        Some(_) => context
+            .accounts
+            .escrow_vault
+            .as_ref()
+            .ok_or(error!(ErrorCode::PaymentAccountsRequired))?
+            .amount,
+        None => event_pda.lamports(),
+    };
+    if !event.escrow_covers_withdrawal(amount, escrow_balance, escrow_rent_reserve(event.payment_mint)?) {
+        return Err(error!(ErrorCode::InsufficientEscrowBalance));
+    }
+
+    // The platform fee, at the rate the event was created with, goes to the treasury and the rest to the organizer
+    let protocol_fee = event.protocol_fee(amount).ok_or(error!(ErrorCode::CustomError))?;
+    let organizer_amount = amount.checked_sub(protocol_fee).ok_or(error!(ErrorCode::CustomError))?;
+
+    let event_key = event.key();
+    let bump = &[context.bumps.event_pda];
+    let event_pda_seeds: &[&[u8]] = &[b"escrow", event_key.as_ref(), bump];
+    if protocol_fee > 0 {
+        transfer_payment(
+            event.payment_mint,
+            &event_pda.to_account_info(),
+            &context.accounts.treasury.to_account_info(),
+            TokenPaymentAccounts::from_optional(
+                &context.accounts.payment_mint,
+                &context.accounts.escrow_vault,
+                &context.accounts.treasury_token_account,
+                &context.accounts.token_program,
+            ),
+            protocol_fee,
+            &context.accounts.system_program.to_account_info(),
+            Some(event_pda_seeds),
+        )?;
+    }
+    transfer_payment(
+        event.payment_mint,
+        &event_pda.to_account_info(),
+        &context.accounts.organizer.to_account_info(),
+        TokenPaymentAccounts::from_optional(
+            &context.accounts.payment_mint,
+            &context.accounts.escrow_vault,
+            &context.accounts.organizer_token_account,
+            &context.accounts.token_program,
+        ),
+        organizer_amount,
+        &context.accounts.system_program.to_account_info(),
+        Some(event_pda_seeds),
+    )?;
+
+    event.proceeds_withdrawn = total_proceeds;
+
+    emit!(ProceedsWithdrawn {
+        event: event_key,
+        organizer: event.organizer,
+        amount,
+        protocol_fee,
+        total_withdrawn: event.proceeds_withdrawn,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetGateAuthorityAccountConstraints<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
+    )]
+    pub event: Account<'info, Event>,
+}
+
+pub fn set_gate_authority(
+    context: Context<SetGateAuthorityAccountConstraints>,
+    gate_authority: Pubkey,
+    check_in_opens_before: i64,
+    check_in_closes_after: i64,
+) -> Result<()> {
+    let event = &mut context.accounts.event;
+
+    // The window is measured from the event start, in either direction
+    let window = 0..=MAX_CHECK_IN_WINDOW;
+    if !window.contains(&check_in_opens_before) || !window.contains(&check_in_closes_after) {
+        return Err(error!(ErrorCode::InvalidCheckInWindow));
+    }
+
+    // Pushing the opening back once check-in has opened would let the organizer cancel with tickets already checked in
+    let now = Clock::get()?.unix_timestamp;
+    if now >= event.check_in_opens_at() && check_in_opens_before < event.check_in_opens_before {
+        return Err(error!(ErrorCode::InvalidCheckInWindow));
+    }
+
+    event.gate_authority = gate_authority;
+    event.check_in_opens_before = check_in_opens_before;
+    event.check_in_closes_after = check_in_closes_after;
+
+    emit!(GateAuthoritySet {
+        event: event.key(),
+        gate_authority,
+        check_in_opens_before,
+        check_in_closes_after,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetResaleTermsAccountConstraints<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
+    )]
+    pub event: Account<'info, Event>,
+}
+
+pub fn set_resale_terms(
+    context: Context<SetResaleTermsAccountConstraints>,
+    resale_cap_bps: u16,
+    resale_royalty_bps: u16,
+) -> Result<()> {
+    let event = &mut context.accounts.event;
+
+    // The royalty is taken out of the resale price, so it cannot exceed all of it
+    if resale_cap_bps == 0 || resale_royalty_bps as u128 > BPS_DENOMINATOR {
+        return Err(error!(ErrorCode::InvalidResaleTerms));
+    }
+
+    event.resale_cap_bps = resale_cap_bps;
+    event.resale_royalty_bps = resale_royalty_bps;
+
+    emit!(ResaleTermsSet {
+        event: event.key(),
+        resale_cap_bps,
+        resale_royalty_bps,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPresaleAccountConstraints<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(seeds = [b"protocol_config"], bump = protocol_config.bump)]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+pub fn set_presale(
+    context: Context<SetPresaleAccountConstraints>,
+    allowlist_root: [u8; 32],
+    presale_start_time: i64,
+    presale_discount_bps: u16,
+) -> Result<()> {
+    let event = &mut context.accounts.event;
+
+    // Get current time
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    // Allowlisted wallets must not find the rules changed under them
+    let presale_started = event.presale_start_time != 0 && now >= event.presale_start_time;
+    if presale_started || now >= event.auction_start_time {
+        return Err(error!(ErrorCode::PresaleAlreadyStarted));
+    }
+    if event.status != EventStatus::Created && event.status != EventStatus::Active {
+        return Err(error!(ErrorCode::InvalidPresale));
+    }
+    // The presale sells at a fixed price off each tier's Dutch auction start price
+    if event.is_sealed_bid() {
+        return Err(error!(ErrorCode::InvalidPresale));
+    }
+
+    // A start time of 0 turns the presale off; otherwise it has to end when the auction starts
+    if presale_start_time != 0 && (presale_start_time < now || presale_start_time >= event.auction_start_time) {
+        return Err(error!(ErrorCode::InvalidPresale));
+    }
+    if presale_discount_bps as u128 > BPS_DENOMINATOR {
+        return Err(error!(ErrorCode::InvalidPresale));
+    }
+    // Presale prices stop at each tier's reserve, and must still be a price the protocol allows
+    let min_ticket_price = context.accounts.protocol_config.limits.min_ticket_price;
+    let below_minimum = event.tiers.iter().any(|tier| {
+        let price = tier.presale_price(presale_discount_bps);
+        price == 0 || price < min_ticket_price
+    });
+    if below_minimum {
+        return Err(error!(ErrorCode::InvalidPresale));
+    }
+
+    event.allowlist_root = allowlist_root;
+    event.presale_start_time = presale_start_time;
+    event.presale_discount_bps = presale_discount_bps;
+
+    emit!(PresaleSet {
+        event: event.key(),
+        allowlist_root,
+        presale_start_time,
+        presale_discount_bps,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetAttestationIssuerAccountConstraints<'info> {
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
+    )]
+    pub event: Account<'info, Event>,
+}
+
+pub fn set_attestation_issuer(
+    context: Context<SetAttestationIssuerAccountConstraints>,
+    attestation_issuer: Option<Pubkey>,
+) -> Result<()> {
+    let event = &mut context.accounts.event;
+
+    // Bids placed so far were not attested, so the rule cannot change once bidding is possible
+    if event.status != EventStatus::Created {
+        return Err(error!(ErrorCode::InvalidEventStatusTransition));
+    }
+
+    event.attestation_issuer = attestation_issuer;
+
+    emit!(AttestationIssuerSet {
+        event: event.key(),
+        attestation_issuer,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetAuctionTypeAccountConstraints<'info> {
+    #[account(mut)]
+    pub organizer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
+    )]
+    pub event: Account<'info, Event>,
+    /// Ranking of the revealed bids in every tier, required for a sealed-bid auction
+    #[account(
+        init_if_needed,
+        payer = organizer,
+        space = 8 + BidRanking::space(&event.tiers), // 8 bytes for discriminator
+        seeds = [b"bid_ranking", event.key().as_ref()],
+        bump
+    )]
+    pub bid_ranking: Option<Account<'info, BidRanking>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_auction_type(
+    context: Context<SetAuctionTypeAccountConstraints>,
+    auction_type: AuctionType,
+) -> Result<()> {
+    let event = &mut context.accounts.event;
+
+    // Bids are placed one way or the other, so the auction type is fixed once bidding is possible
+    if event.status != EventStatus::Created {
+        return Err(error!(ErrorCode::InvalidEventStatusTransition));
+    }
+
+    if let AuctionType::SealedBid { reveal_end_time } = auction_type {
+        // Reveals run after bidding ends and are over before the event starts
+        if reveal_end_time <= event.auction_end_time || reveal_end_time > event.event_start_time {
+            return Err(error!(ErrorCode::InvalidAuctionType));
+        }
+        // The presale only exists for Dutch auctions
+        if event.presale_start_time != 0 {
+            return Err(error!(ErrorCode::InvalidAuctionType));
+        }
+
+        // Revealed bids are ranked on-chain, so only the top ones in each tier can be awarded
+        let bid_ranking = context.accounts.bid_ranking.as_mut().ok_or(error!(ErrorCode::BidRankingRequired))?;
+        bid_ranking.event = event.key();
+        bid_ranking.tiers = vec![TierRanking::default(); event.tiers.len()];
+        bid_ranking.bump = context.bumps.bid_ranking.ok_or(error!(ErrorCode::BidRankingRequired))?;
+    }
+
+    event.auction_type = auction_type;
+
+    emit!(AuctionTypeSet {
+        event: event.key(),
+        organizer: event.organizer,
+        auction_type,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,188 @@
+//! Ticketfair protocol config instruction handlers
+
+use anchor_lang::prelude::*;
+use crate::state::{ProtocolConfig, ProtocolLimits};
+use crate::error::ErrorCode;
+use crate::events::{ProtocolAdminAccepted, ProtocolAdminProposed, ProtocolConfigCreated, ProtocolFeeSet, ProtocolLimitsSet};
+use crate::program::Escrow;
+
+#[derive(Accounts)]
+pub struct CreateProtocolConfigAccountConstraints<'info> {
+    /// The program's upgrade authority, who becomes the first admin
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = ProtocolConfig::DISCRIMINATOR.len() + ProtocolConfig::INIT_SPACE,
+        seeds = [b"protocol_config"],
+        bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::NotUpgradeAuthority)]
+    pub program: Program<'info, Escrow>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::NotUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_protocol_config(
+    context: Context<CreateProtocolConfigAccountConstraints>,
+    fee_bps: u16,
+    treasury: Pubkey,
+    limits: ProtocolLimits,
+) -> Result<()> {
+    let protocol_config = &mut context.accounts.protocol_config;
+
+    if !ProtocolConfig::is_valid_fee(fee_bps) {
+        return Err(error!(ErrorCode::InvalidProtocolFee));
+    }
+    if !limits.is_valid() {
+        return Err(error!(ErrorCode::InvalidProtocolLimits));
+    }
+
+    protocol_config.admin = context.accounts.admin.key();
+    protocol_config.pending_admin = None;
+    protocol_config.fee_bps = fee_bps;
+    protocol_config.treasury = treasury;
+    protocol_config.limits = limits;
+    protocol_config.bump = context.bumps.protocol_config;
+
+    emit!(ProtocolConfigCreated {
+        protocol_config: protocol_config.key(),
+        admin: protocol_config.admin,
+        fee_bps,
+        treasury,
+        limits,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFeeAccountConstraints<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        has_one = admin @ ErrorCode::NotProtocolAdmin,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+pub fn set_protocol_fee(
+    context: Context<SetProtocolFeeAccountConstraints>,
+    fee_bps: u16,
+    treasury: Pubkey,
+) -> Result<()> {
+    let protocol_config = &mut context.accounts.protocol_config;
+
+    // Events keep the fee they were created with, so this only applies to new events
+    if !ProtocolConfig::is_valid_fee(fee_bps) {
+        return Err(error!(ErrorCode::InvalidProtocolFee));
+    }
+
+    protocol_config.fee_bps = fee_bps;
+    protocol_config.treasury = treasury;
+
+    emit!(ProtocolFeeSet {
+        protocol_config: protocol_config.key(),
+        fee_bps,
+        treasury,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolLimitsAccountConstraints<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        has_one = admin @ ErrorCode::NotProtocolAdmin,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+pub fn set_protocol_limits(
+    context: Context<SetProtocolLimitsAccountConstraints>,
+    limits: ProtocolLimits,
+) -> Result<()> {
+    let protocol_config = &mut context.accounts.protocol_config;
+
+    if !limits.is_valid() {
+        return Err(error!(ErrorCode::InvalidProtocolLimits));
+    }
+
+    protocol_config.limits = limits;
+
+    emit!(ProtocolLimitsSet {
+        protocol_config: protocol_config.key(),
+        limits,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProposeProtocolAdminAccountConstraints<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        has_one = admin @ ErrorCode::NotProtocolAdmin,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+pub fn propose_protocol_admin(
+    context: Context<ProposeProtocolAdminAccountConstraints>,
+    pending_admin: Option<Pubkey>,
+) -> Result<()> {
+    let protocol_config = &mut context.accounts.protocol_config;
+
+    // The current admin stays in charge until the proposed one accepts, so a mistyped key can be replaced
+    protocol_config.pending_admin = pending_admin;
+
+    emit!(ProtocolAdminProposed {
+        protocol_config: protocol_config.key(),
+        admin: protocol_config.admin,
+        pending_admin,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptProtocolAdminAccountConstraints<'info> {
+    pub pending_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = protocol_config.pending_admin == Some(pending_admin.key()) @ ErrorCode::NotPendingProtocolAdmin,
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+}
+
+pub fn accept_protocol_admin(
+    context: Context<AcceptProtocolAdminAccountConstraints>,
+) -> Result<()> {
+    let protocol_config = &mut context.accounts.protocol_config;
+
+    let previous_admin = protocol_config.admin;
+    protocol_config.admin = context.accounts.pending_admin.key();
+    protocol_config.pending_admin = None;
+
+    emit!(ProtocolAdminAccepted {
+        protocol_config: protocol_config.key(),
+        previous_admin,
+        admin: protocol_config.admin,
+    });
+
+    Ok(())
+}
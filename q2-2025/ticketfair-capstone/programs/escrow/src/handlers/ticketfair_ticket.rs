@@ -1,34 +1,812 @@
 //! Ticketfair ticket instruction handlers
 
 use anchor_lang::prelude::*;
-use crate::state::{Ticket, Event};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{transition, EventStatus, Listing, Ticket, TicketInventory, TicketStatus, Event};
+use crate::error::ErrorCode;
+use crate::events::{TicketBought, TicketCheckedIn, TicketClosed, TicketDelisted, TicketListed, TicketRefunded, TicketResold};
+use crate::handlers::shared::{transfer_payment, CnftTransferArgs, TokenPaymentAccounts};
+#[cfg(feature = "bubblegum")]
+use crate::handlers::shared::transfer_cnft;
 
 #[derive(Accounts)]
+#[instruction(tier: u8, ticket_index: u32)]
 pub struct BuyTicketAccountConstraints<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+    )]
     pub event: Account<'info, Event>,
+    /// The PDA that holds escrowed funds for the event
+    /// Seeds: [b"escrow", event.key().as_ref()]
+    #[account(mut, seeds = [b"escrow", event.key().as_ref()], bump)]
+    pub event_pda: SystemAccount<'info>,
     #[account(
         init,
         payer = buyer,
         space = Ticket::DISCRIMINATOR.len() + Ticket::INIT_SPACE,
-        seeds = [b"ticket", event.key().as_ref(), buyer.key().as_ref()],
+        seeds = [b"ticket", event.key().as_ref(), buyer.key().as_ref(), tier.to_le_bytes().as_ref(), ticket_index.to_le_bytes().as_ref()],
         bump
     )]
     pub ticket: Account<'info, Ticket>,
+    /// The inventory page holding the ticket's cNFT
+    #[account(
+        mut,
+        seeds = [b"inventory", inventory.event.as_ref(), inventory.page.to_le_bytes().as_ref()],
+        bump = inventory.bump,
+        has_one = event @ ErrorCode::InventoryNotForEvent,
+    )]
+    pub inventory: Box<Account<'info, TicketInventory>>,
+    /// Payment mint, required when the event is paid in tokens
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Buyer's token account for the payment mint
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = buyer,
+        token::token_program = token_program,
+    )]
+    pub buyer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Escrow token vault owned by the event PDA
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = event_pda,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    /// Bubblegum Merkle Tree for cNFTs
+    /// CHECK: Verified in Bubblegum program CPI call
+    #[account(mut, address = event.merkle_tree @ ErrorCode::MerkleTreeMismatch)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// Bubblegum tree config PDA for the Merkle Tree
+    /// CHECK: Verified in Bubblegum program CPI call
+    pub tree_config: UncheckedAccount<'info>,
+    /// Bubblegum program
+    /// CHECK: Program ID verified in CPI
+    pub bubblegum_program: UncheckedAccount<'info>,
+    /// Log wrapper program (required by Bubblegum)
+    /// CHECK: Program ID verified in CPI
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// Compression program (required by Bubblegum)
+    /// CHECK: Program ID verified in CPI
+    pub compression_program: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn buy_ticket(
-    context: Context<BuyTicketAccountConstraints>,
+/// Fixed-price sale of a tier's supply left over after the auction, at the tier's auction close price.
+/// Remaining accounts: the Merkle proof path for `cnft_asset_id` (read-only), when the `bubblegum` feature is enabled.
+pub fn buy_ticket<'info>(
+    context: Context<'_, '_, 'info, 'info, BuyTicketAccountConstraints<'info>>,
+    tier: u8,
+    ticket_index: u32,
     offchain_ref: String,
+    cnft_asset_id: Pubkey,
+    transfer_args: CnftTransferArgs,
 ) -> Result<()> {
+    let event = &mut context.accounts.event;
     let ticket = &mut context.accounts.ticket;
-    ticket.owner = context.accounts.buyer.key();
-    ticket.event = context.accounts.event.key();
-    ticket.status = 0;
+    let buyer = &context.accounts.buyer;
+
+    // Unsold inventory only goes on sale once the auction has set its price
+    if event.status != EventStatus::Finalized {
+        return Err(error!(ErrorCode::EventNotFinalized));
+    }
+    if event.tier(tier)?.is_sold_out() {
+        return Err(error!(ErrorCode::TicketsSoldOut));
+    }
+
+    // Ticket numbers stop at the per-wallet limit, so a wallet can never hold more tickets of a tier than that
+    if !event.is_within_wallet_limit(ticket_index.saturating_add(1)) {
+        return Err(error!(ErrorCode::WalletLimitExceeded));
+    }
+
+    // The asset must be one of this tier's cNFTs and must not have been awarded already
+    let asset_index = context.accounts.inventory.find_unawarded_asset(&cnft_asset_id, &event.tier_tickets(tier)?)?;
+
+    // Everyone pays the same price the tier's auction winners did
+    let price = event.tier(tier)?.close_price;
+
+    // Charge the buyer into escrow, where it is counted towards the organizer's proceeds
+    transfer_payment(
+        event.payment_mint,
+        &buyer.to_account_info(),
+        &context.accounts.event_pda.to_account_info(),
+        TokenPaymentAccounts::from_optional(
+            &context.accounts.payment_mint,
+            &context.accounts.buyer_token_account,
+            &context.accounts.escrow_vault,
+            &context.accounts.token_program,
+        ),
+        price,
+        &context.accounts.system_program.to_account_info(),
+        None,
+    )?;
+    event.total_escrowed = event.total_escrowed.checked_add(price).ok_or(error!(ErrorCode::CustomError))?;
+
+    // Bubblegum CPI: Transfer cNFT from event PDA to buyer
+    #[cfg(feature = "bubblegum")]
+    {
+        let event_id_bytes = event.event_id.to_le_bytes();
+        let bump = [event.bump];
+        let event_pda_seeds: &[&[u8]] = &[b"event", event.organizer.as_ref(), &event_id_bytes, &bump];
+
+        transfer_cnft(
+            &context.accounts.bubblegum_program.to_account_info(),
+            &context.accounts.tree_config.to_account_info(),
+            &buyer.to_account_info(),
+            &event.to_account_info(), // event PDA as current owner
+            &buyer.to_account_info(), // new owner (buyer)
+            &context.accounts.merkle_tree.to_account_info(),
+            &context.accounts.log_wrapper.to_account_info(),
+            &context.accounts.compression_program.to_account_info(),
+            &context.accounts.system_program.to_account_info(),
+            context.remaining_accounts,
+            &transfer_args,
+            &cnft_asset_id,
+            Some(event_pda_seeds),
+        )?;
+    }
+
+    #[cfg(not(feature = "bubblegum"))]
+    msg!(
+        "Bubblegum feature not enabled - simulating cNFT transfer for asset ID: {} (leaf {})",
+        cnft_asset_id,
+        transfer_args.index
+    );
+
+    context.accounts.inventory.awarded[asset_index] = true;
+    event.record_awarded_ticket(tier)?;
+
+    ticket.owner = buyer.key();
+    ticket.event = event.key();
+    ticket.status = TicketStatus::Owned;
     ticket.offchain_ref = offchain_ref;
     ticket.bump = context.bumps.ticket;
+    ticket.cnft_asset_id = cnft_asset_id;
+    ticket.index = ticket_index;
+    ticket.tier = tier;
+    ticket.rent_payer = buyer.key();
+    ticket.purchase_price = price;
+
+    emit!(TicketBought {
+        event: ticket.event,
+        buyer: ticket.owner,
+        ticket: ticket.key(),
+        tier,
+        cnft_asset_id,
+        price,
+        tickets_awarded: event.tickets_awarded,
+        status: ticket.status,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RefundTicketAccountConstraints<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [b"ticket", ticket.event.as_ref(), ticket.owner.as_ref(), ticket.tier.to_le_bytes().as_ref(), ticket.index.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+        has_one = event @ ErrorCode::TicketNotForEvent,
+        has_one = owner @ ErrorCode::TicketOwnerMismatch,
+    )]
+    pub ticket: Account<'info, Ticket>,
+    /// Event PDA (escrow authority)
+    #[account(mut, seeds = [b"escrow", event.key().as_ref()], bump)]
+    pub event_pda: SystemAccount<'info>,
+    /// Payment mint, required when the event is paid in tokens
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Owner's token account for the payment mint
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = owner,
+        token::token_program = token_program,
+    )]
+    pub owner_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Escrow token vault owned by the event PDA
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = event_pda,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Refund a ticket bought at a fixed price once its event is cancelled. Awarded tickets are refunded through their bid instead.
+pub fn refund_ticket(
+    context: Context<RefundTicketAccountConstraints>,
+) -> Result<()> {
+    let event = &mut context.accounts.event;
+    let ticket = &mut context.accounts.ticket;
+
+    if event.status != EventStatus::Cancelled {
+        return Err(error!(ErrorCode::EventNotCancelled));
+    }
+    if !ticket.can_refund_purchase() {
+        return Err(error!(ErrorCode::TicketNotRefundable));
+    }
+
+    let refund_amount = ticket.purchase_price;
+    let event_key = event.key();
+    let bump = &[context.bumps.event_pda];
+    let event_pda_seeds: &[&[u8]] = &[b"escrow", event_key.as_ref(), bump];
+    transfer_payment(
+        event.payment_mint,
+        &context.accounts.event_pda.to_account_info(),
+        &context.accounts.owner.to_account_info(),
+        TokenPaymentAccounts::from_optional(
+            &context.accounts.payment_mint,
+            &context.accounts.escrow_vault,
+            &context.accounts.owner_token_account,
+            &context.accounts.token_program,
+        ),
+        refund_amount,
+        &context.accounts.system_program.to_account_info(),
+        Some(event_pda_seeds),
+    )?;
+    event.total_refunded = event.total_refunded.checked_add(refund_amount).ok_or(error!(ErrorCode::CustomError))?;
+
+    transition(&mut ticket.status, TicketStatus::Refunded)?;
+
+    emit!(TicketRefunded {
+        event: event_key,
+        ticket: ticket.key(),
+        owner: ticket.owner,
+        refund_amount,
+        status: ticket.status,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct CheckInTicketAccountConstraints<'info> {
+    pub gate_authority: Signer<'info>,
+    #[account(
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = gate_authority @ ErrorCode::InvalidGateAuthority,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [b"ticket", ticket.event.as_ref(), ticket.owner.as_ref(), ticket.tier.to_le_bytes().as_ref(), ticket.index.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+        has_one = event @ ErrorCode::TicketNotForEvent,
+    )]
+    pub ticket: Account<'info, Ticket>,
+}
+
+pub fn check_in_ticket(
+    context: Context<CheckInTicketAccountConstraints>,
+) -> Result<()> {
+    let event = &context.accounts.event;
+    let ticket = &mut context.accounts.ticket;
+
+    // Get current time
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    if !event.is_check_in_open(now) {
+        return Err(error!(ErrorCode::CheckInNotOpen));
+    }
+
+    // A second scan of the same ticket gets its own error so the gate can tell it apart
+    if ticket.status == TicketStatus::Claimed {
+        return Err(error!(ErrorCode::TicketAlreadyCheckedIn));
+    }
+    if !ticket.can_claim() {
+        return Err(error!(ErrorCode::TicketNotClaimable));
+    }
+
+    transition(&mut ticket.status, TicketStatus::Claimed)?;
+
+    emit!(TicketCheckedIn {
+        event: event.key(),
+        ticket: ticket.key(),
+        owner: ticket.owner,
+        gate_authority: context.accounts.gate_authority.key(),
+        status: ticket.status,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ListTicketAccountConstraints<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [b"ticket", ticket.event.as_ref(), ticket.owner.as_ref(), ticket.tier.to_le_bytes().as_ref(), ticket.index.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+        has_one = event @ ErrorCode::TicketNotForEvent,
+        constraint = ticket.owner == seller.key() @ ErrorCode::TicketOwnerMismatch,
+    )]
+    pub ticket: Account<'info, Ticket>,
+    #[account(
+        init,
+        payer = seller,
+        space = Listing::DISCRIMINATOR.len() + Listing::INIT_SPACE,
+        seeds = [b"listing", ticket.key().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+    /// Bubblegum Merkle Tree for cNFTs
+    /// CHECK: Verified in Bubblegum program CPI call
+    #[account(mut, address = event.merkle_tree @ ErrorCode::MerkleTreeMismatch)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// Bubblegum tree config PDA for the Merkle Tree
+    /// CHECK: Verified in Bubblegum program CPI call
+    pub tree_config: UncheckedAccount<'info>,
+    /// Bubblegum program
+    /// CHECK: Program ID verified in CPI
+    pub bubblegum_program: UncheckedAccount<'info>,
+    /// Log wrapper program (required by Bubblegum)
+    /// CHECK: Program ID verified in CPI
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// Compression program (required by Bubblegum)
+    /// CHECK: Program ID verified in CPI
+    pub compression_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Remaining accounts: the Merkle proof path for the ticket's cNFT (read-only), when the `bubblegum` feature is enabled.
+pub fn list_ticket<'info>(
+    context: Context<'_, '_, 'info, 'info, ListTicketAccountConstraints<'info>>,
+    price: u64,
+    transfer_args: CnftTransferArgs,
+) -> Result<()> {
+    let event = &context.accounts.event;
+    let ticket = &mut context.accounts.ticket;
+    let listing = &mut context.accounts.listing;
+    let seller = &context.accounts.seller;
+
+    // Get current time
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    if !event.is_resale_open(now) {
+        return Err(error!(ErrorCode::ResaleNotOpen));
+    }
+    if !ticket.can_list() {
+        return Err(error!(ErrorCode::TicketNotListable));
+    }
+
+    // The organizer's cap keeps resale prices close to what the ticket's tier cleared at
+    let max_price = event.max_resale_price(ticket.tier)?;
+    if price == 0 || price > max_price {
+        return Err(error!(ErrorCode::ResalePriceAboveCap));
+    }
+
+    // Bubblegum CPI: hold the cNFT in the event PDA while it is listed
+    #[cfg(feature = "bubblegum")]
+    transfer_cnft(
+        &context.accounts.bubblegum_program.to_account_info(),
+        &context.accounts.tree_config.to_account_info(),
+        &seller.to_account_info(),
+        &seller.to_account_info(), // seller as current owner
+        &event.to_account_info(), // event PDA holds the listed cNFT
+        &context.accounts.merkle_tree.to_account_info(),
+        &context.accounts.log_wrapper.to_account_info(),
+        &context.accounts.compression_program.to_account_info(),
+        &context.accounts.system_program.to_account_info(),
+        context.remaining_accounts,
+        &transfer_args,
+        &ticket.cnft_asset_id,
+        None,
+    )?;
+
+    #[cfg(not(feature = "bubblegum"))]
+    msg!(
+        "Bubblegum feature not enabled - simulating cNFT transfer for asset ID: {} (leaf {})",
+        ticket.cnft_asset_id,
+        transfer_args.index
+    );
+
+    transition(&mut ticket.status, TicketStatus::Listed)?;
+
+    listing.seller = seller.key();
+    listing.event = event.key();
+    listing.ticket = ticket.key();
+    listing.price = price;
+    listing.bump = context.bumps.listing;
+
+    emit!(TicketListed {
+        event: listing.event,
+        ticket: listing.ticket,
+        listing: listing.key(),
+        seller: listing.seller,
+        price,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DelistTicketAccountConstraints<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        seeds = [b"ticket", ticket.event.as_ref(), ticket.owner.as_ref(), ticket.tier.to_le_bytes().as_ref(), ticket.index.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+        has_one = event @ ErrorCode::TicketNotForEvent,
+        constraint = ticket.owner == seller.key() @ ErrorCode::TicketOwnerMismatch,
+    )]
+    pub ticket: Account<'info, Ticket>,
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", listing.ticket.as_ref()],
+        bump = listing.bump,
+        has_one = ticket @ ErrorCode::ListingNotForTicket,
+        has_one = seller @ ErrorCode::NotListingSeller,
+    )]
+    pub listing: Account<'info, Listing>,
+    /// Bubblegum Merkle Tree for cNFTs
+    /// CHECK: Verified in Bubblegum program CPI call
+    #[account(mut, address = event.merkle_tree @ ErrorCode::MerkleTreeMismatch)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// Bubblegum tree config PDA for the Merkle Tree
+    /// CHECK: Verified in Bubblegum program CPI call
+    pub tree_config: UncheckedAccount<'info>,
+    /// Bubblegum program
+    /// CHECK: Program ID verified in CPI
+    pub bubblegum_program: UncheckedAccount<'info>,
+    /// Log wrapper program (required by Bubblegum)
+    /// CHECK: Program ID verified in CPI
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// Compression program (required by Bubblegum)
+    /// CHECK: Program ID verified in CPI
+    pub compression_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Remaining accounts: the Merkle proof path for the ticket's cNFT (read-only), when the `bubblegum` feature is enabled.
+pub fn delist_ticket<'info>(
+    context: Context<'_, '_, 'info, 'info, DelistTicketAccountConstraints<'info>>,
+    transfer_args: CnftTransferArgs,
+) -> Result<()> {
+    let event = &context.accounts.event;
+    let ticket = &mut context.accounts.ticket;
+    let seller = &context.accounts.seller;
+
+    // Bubblegum CPI: return the cNFT from the event PDA to the seller
+    #[cfg(feature = "bubblegum")]
+    {
+        let event_id_bytes = event.event_id.to_le_bytes();
+        let bump = [event.bump];
+        let event_pda_seeds: &[&[u8]] = &[b"event", event.organizer.as_ref(), &event_id_bytes, &bump];
+
+        transfer_cnft(
+            &context.accounts.bubblegum_program.to_account_info(),
+            &context.accounts.tree_config.to_account_info(),
+            &seller.to_account_info(),
+            &event.to_account_info(), // event PDA as current owner
+            &seller.to_account_info(), // back to the seller
+            &context.accounts.merkle_tree.to_account_info(),
+            &context.accounts.log_wrapper.to_account_info(),
+            &context.accounts.compression_program.to_account_info(),
+            &context.accounts.system_program.to_account_info(),
+            context.remaining_accounts,
+            &transfer_args,
+            &ticket.cnft_asset_id,
+            Some(event_pda_seeds),
+        )?;
+    }
+
+    #[cfg(not(feature = "bubblegum"))]
+    msg!(
+        "Bubblegum feature not enabled - simulating cNFT transfer for asset ID: {} (leaf {})",
+        ticket.cnft_asset_id,
+        transfer_args.index
+    );
+
+    transition(&mut ticket.status, TicketStatus::Owned)?;
+
+    emit!(TicketDelisted {
+        event: event.key(),
+        ticket: ticket.key(),
+        seller: seller.key(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_index: u32)]
+pub struct BuyListedTicketAccountConstraints<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    /// CHECK: Checked against the listing
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+    /// CHECK: Checked against the event, receives the royalty
+    #[account(mut)]
+    pub organizer: UncheckedAccount<'info>,
+    /// Whoever paid the rent for the seller's ticket, and gets it back
+    /// CHECK: Checked against the seller's ticket
+    #[account(mut)]
+    pub ticket_rent_payer: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+        has_one = organizer @ ErrorCode::NotEventOrganizer,
+    )]
+    pub event: Account<'info, Event>,
+    /// The seller's ticket, closed as ownership moves to the buyer
+    #[account(
+        mut,
+        close = ticket_rent_payer,
+        seeds = [b"ticket", ticket.event.as_ref(), ticket.owner.as_ref(), ticket.tier.to_le_bytes().as_ref(), ticket.index.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+        has_one = event @ ErrorCode::TicketNotForEvent,
+        constraint = ticket.owner == seller.key() @ ErrorCode::TicketOwnerMismatch,
+        constraint = ticket.rent_payer == ticket_rent_payer.key() @ ErrorCode::RentPayerMismatch,
+    )]
+    pub ticket: Box<Account<'info, Ticket>>,
+    /// The buyer's ticket, re-keyed to the buyer in the same tier
+    #[account(
+        init,
+        payer = buyer,
+        space = Ticket::DISCRIMINATOR.len() + Ticket::INIT_SPACE,
+        seeds = [b"ticket", event.key().as_ref(), buyer.key().as_ref(), ticket.tier.to_le_bytes().as_ref(), ticket_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub buyer_ticket: Box<Account<'info, Ticket>>,
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", listing.ticket.as_ref()],
+        bump = listing.bump,
+        has_one = ticket @ ErrorCode::ListingNotForTicket,
+        has_one = seller @ ErrorCode::NotListingSeller,
+    )]
+    pub listing: Account<'info, Listing>,
+    /// Payment mint, required when the event is paid in tokens
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Buyer's token account for the payment mint
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = buyer,
+        token::token_program = token_program,
+    )]
+    pub buyer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Seller's token account for the payment mint
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = seller,
+        token::token_program = token_program,
+    )]
+    pub seller_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Organizer's token account for the payment mint, receives the royalty
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = organizer,
+        token::token_program = token_program,
+    )]
+    pub organizer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    /// Bubblegum Merkle Tree for cNFTs
+    /// CHECK: Verified in Bubblegum program CPI call
+    #[account(mut, address = event.merkle_tree @ ErrorCode::MerkleTreeMismatch)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// Bubblegum tree config PDA for the Merkle Tree
+    /// CHECK: Verified in Bubblegum program CPI call
+    pub tree_config: UncheckedAccount<'info>,
+    /// Bubblegum program
+    /// CHECK: Program ID verified in CPI
+    pub bubblegum_program: UncheckedAccount<'info>,
+    /// Log wrapper program (required by Bubblegum)
+    /// CHECK: Program ID verified in CPI
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// Compression program (required by Bubblegum)
+    /// CHECK: Program ID verified in CPI
+    pub compression_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Remaining accounts: the Merkle proof path for the ticket's cNFT (read-only), when the `bubblegum` feature is enabled.
+pub fn buy_listed_ticket<'info>(
+    context: Context<'_, '_, 'info, 'info, BuyListedTicketAccountConstraints<'info>>,
+    ticket_index: u32,
+    transfer_args: CnftTransferArgs,
+) -> Result<()> {
+    let event = &context.accounts.event;
+    let ticket = &context.accounts.ticket;
+    let buyer_ticket = &mut context.accounts.buyer_ticket;
+    let listing = &context.accounts.listing;
+    let buyer = &context.accounts.buyer;
+
+    // Get current time
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+
+    // Listings left open when the event starts can no longer be bought
+    if !event.is_resale_open(now) {
+        return Err(error!(ErrorCode::ResaleNotOpen));
+    }
+    if !event.is_within_wallet_limit(ticket_index.saturating_add(1)) {
+        return Err(error!(ErrorCode::WalletLimitExceeded));
+    }
+
+    // Royalty to the organizer, the rest to the seller
+    let royalty = event.resale_royalty(listing.price).ok_or(error!(ErrorCode::CustomError))?;
+    let seller_amount = listing.price.checked_sub(royalty).ok_or(error!(ErrorCode::CustomError))?;
+
+    if royalty > 0 {
+        transfer_payment(
+            event.payment_mint,
+            &buyer.to_account_info(),
+            &context.accounts.organizer.to_account_info(),
+            TokenPaymentAccounts::from_optional(
+                &context.accounts.payment_mint,
+                &context.accounts.buyer_token_account,
+                &context.accounts.organizer_token_account,
+                &context.accounts.token_program,
+            ),
+            royalty,
+            &context.accounts.system_program.to_account_info(),
+            None,
+        )?;
+    }
+    transfer_payment(
+        event.payment_mint,
+        &buyer.to_account_info(),
+        &context.accounts.seller.to_account_info(),
+        TokenPaymentAccounts::from_optional(
+            &context.accounts.payment_mint,
+            &context.accounts.buyer_token_account,
+            &context.accounts.seller_token_account,
+            &context.accounts.token_program,
+        ),
+        seller_amount,
+        &context.accounts.system_program.to_account_info(),
+        None,
+    )?;
+
+    // Bubblegum CPI: Transfer cNFT from event PDA to buyer
+    #[cfg(feature = "bubblegum")]
+    {
+        let event_id_bytes = event.event_id.to_le_bytes();
+        let bump = [event.bump];
+        let event_pda_seeds: &[&[u8]] = &[b"event", event.organizer.as_ref(), &event_id_bytes, &bump];
+
+        transfer_cnft(
+            &context.accounts.bubblegum_program.to_account_info(),
+            &context.accounts.tree_config.to_account_info(),
+            &buyer.to_account_info(),
+            &event.to_account_info(), // event PDA as current owner
+            &buyer.to_account_info(), // new owner (buyer)
+            &context.accounts.merkle_tree.to_account_info(),
+            &context.accounts.log_wrapper.to_account_info(),
+            &context.accounts.compression_program.to_account_info(),
+            &context.accounts.system_program.to_account_info(),
+            context.remaining_accounts,
+            &transfer_args,
+            &ticket.cnft_asset_id,
+            Some(event_pda_seeds),
+        )?;
+    }
+
+    #[cfg(not(feature = "bubblegum"))]
+    msg!(
+        "Bubblegum feature not enabled - simulating cNFT transfer for asset ID: {} (leaf {})",
+        ticket.cnft_asset_id,
+        transfer_args.index
+    );
+
+    // Ticket PDAs are seeded by owner, so the buyer gets a new one and the seller's is closed
+    buyer_ticket.owner = buyer.key();
+    buyer_ticket.event = event.key();
+    buyer_ticket.status = TicketStatus::Owned;
+    buyer_ticket.offchain_ref = ticket.offchain_ref.clone();
+    buyer_ticket.bump = context.bumps.buyer_ticket;
+    buyer_ticket.cnft_asset_id = ticket.cnft_asset_id;
+    buyer_ticket.index = ticket_index;
+    buyer_ticket.tier = ticket.tier;
+    buyer_ticket.rent_payer = buyer.key();
+    // What the escrow holds for the ticket stays with it, whatever the resale price
+    buyer_ticket.purchase_price = ticket.purchase_price;
+
+    emit!(TicketResold {
+        event: event.key(),
+        ticket: buyer_ticket.key(),
+        seller: listing.seller,
+        buyer: buyer.key(),
+        cnft_asset_id: buyer_ticket.cnft_asset_id,
+        price: listing.price,
+        royalty,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseTicketAccountConstraints<'info> {
+    /// The ticket owner, or whoever paid the ticket's rent
+    pub authority: Signer<'info>,
+    /// CHECK: Checked against the ticket, gets the rent back
+    #[account(mut)]
+    pub rent_payer: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"event", event.organizer.as_ref(), event.event_id.to_le_bytes().as_ref()],
+        bump = event.bump,
+    )]
+    pub event: Account<'info, Event>,
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [b"ticket", ticket.event.as_ref(), ticket.owner.as_ref(), ticket.tier.to_le_bytes().as_ref(), ticket.index.to_le_bytes().as_ref()],
+        bump = ticket.bump,
+        has_one = event @ ErrorCode::TicketNotForEvent,
+        has_one = rent_payer @ ErrorCode::RentPayerMismatch,
+        constraint = ticket.owner == authority.key() || ticket.rent_payer == authority.key() @ ErrorCode::TicketOwnerMismatch,
+    )]
+    pub ticket: Account<'info, Ticket>,
+}
+
+pub fn close_ticket(
+    context: Context<CloseTicketAccountConstraints>,
+) -> Result<()> {
+    let event = &context.accounts.event;
+    let ticket = &context.accounts.ticket;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Refunded tickets are done with, used ones once the event can no longer be cancelled, unused ones once it is over
+    if !ticket.can_close(event.has_ended(now), event.can_cancel(now)) {
+        return Err(error!(ErrorCode::TicketNotClosable));
+    }
+
+    emit!(TicketClosed {
+        event: event.key(),
+        ticket: ticket.key(),
+        owner: ticket.owner,
+        rent_payer: ticket.rent_payer,
+        status: ticket.status,
+        timestamp: now,
+    });
+
+    Ok(())
+}
@@ -3,11 +3,13 @@
 
 pub mod constants;
 pub mod error;
+pub mod events;
 pub mod handlers;
 pub mod state;
 
 use anchor_lang::prelude::*;
 use handlers::*;
+use state::{AuctionType, ProtocolLimits, TicketTierArgs};
 
 declare_id!("3XCMusDvagK9wyRaHEMbrhLPQfFQPXhQXZZ7oZ2pr2ah");
 
@@ -35,33 +37,100 @@ pub mod escrow {
         handlers::refund::refund_offer(context)
     }
 
-    /// Create a new Ticketfair event.
+    /// Create a new Ticketfair event with up to `MAX_TIERS_PER_EVENT` ticket tiers, numbered by the organizer's user account event counter.
+    /// With the `bubblegum` feature, this also initializes the event's Merkle tree, which the organizer allocates
+    /// beforehand with `merkle_tree_account_size` bytes for every tier's tickets.
+    /// The organizer funds a SOL escrow with its rent-exempt minimum, which stays in it for good.
     pub fn create_event(
         context: Context<CreateEventAccountConstraints>,
         metadata_url: String,
-        ticket_supply: u32,
-        start_price: u64,
-        end_price: u64,
+        tiers: Vec<TicketTierArgs>,
+        max_tickets_per_wallet: u32,
         auction_start_time: i64,
         auction_end_time: i64,
+        event_start_time: i64,
     ) -> Result<()> {
         handlers::ticketfair_event::create_event(
             context, 
             metadata_url, 
-            ticket_supply, 
-            start_price, 
-            end_price, 
+            tiers, 
+            max_tickets_per_wallet,
             auction_start_time, 
-            auction_end_time
+            auction_end_time,
+            event_start_time
         )
     }
 
-    /// Buy a ticket for a Ticketfair event.
-    pub fn buy_ticket(
-        context: Context<BuyTicketAccountConstraints>,
+    /// Mint `count` of an event's tickets onto its current ticket inventory page.
+    /// Every ticket must be minted before the event can be activated.
+    pub fn mint_tickets(
+        context: Context<MintTicketsAccountConstraints>,
+        count: u32,
+    ) -> Result<()> {
+        handlers::ticketfair_event::mint_tickets(context, count)
+    }
+
+    /// Buy one of a tier's tickets left unsold after a Ticketfair auction, at the tier's auction close price.
+    /// With the `bubblegum` feature, the cNFT Merkle proof is passed in remaining accounts.
+    pub fn buy_ticket<'info>(
+        context: Context<'_, '_, 'info, 'info, BuyTicketAccountConstraints<'info>>,
+        tier: u8,
+        ticket_index: u32,
         offchain_ref: String,
+        cnft_asset_id: Pubkey,
+        transfer_args: CnftTransferArgs,
+    ) -> Result<()> {
+        handlers::ticketfair_ticket::buy_ticket(context, tier, ticket_index, offchain_ref, cnft_asset_id, transfer_args)
+    }
+
+    /// Refund a ticket bought with buy_ticket once its event is cancelled, paying its purchase price back to its owner.
+    pub fn refund_ticket(
+        context: Context<RefundTicketAccountConstraints>,
+    ) -> Result<()> {
+        handlers::ticketfair_ticket::refund_ticket(context)
+    }
+
+    /// Create the program-wide protocol config, with its platform fee, treasury and event limits.
+    /// Only the program's upgrade authority can create it, and becomes its first admin.
+    pub fn create_protocol_config(
+        context: Context<CreateProtocolConfigAccountConstraints>,
+        fee_bps: u16,
+        treasury: Pubkey,
+        limits: ProtocolLimits,
+    ) -> Result<()> {
+        handlers::ticketfair_protocol::create_protocol_config(context, fee_bps, treasury, limits)
+    }
+
+    /// Set the platform fee taken from the proceeds of events created from now on, and the treasury it is paid to.
+    pub fn set_protocol_fee(
+        context: Context<SetProtocolFeeAccountConstraints>,
+        fee_bps: u16,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        handlers::ticketfair_protocol::set_protocol_fee(context, fee_bps, treasury)
+    }
+
+    /// Set the limits new events are checked against.
+    pub fn set_protocol_limits(
+        context: Context<SetProtocolLimitsAccountConstraints>,
+        limits: ProtocolLimits,
+    ) -> Result<()> {
+        handlers::ticketfair_protocol::set_protocol_limits(context, limits)
+    }
+
+    /// Propose a new protocol admin, who takes over once they accept, or withdraw the proposal with None.
+    pub fn propose_protocol_admin(
+        context: Context<ProposeProtocolAdminAccountConstraints>,
+        pending_admin: Option<Pubkey>,
+    ) -> Result<()> {
+        handlers::ticketfair_protocol::propose_protocol_admin(context, pending_admin)
+    }
+
+    /// Accept the protocol admin role as the proposed admin.
+    pub fn accept_protocol_admin(
+        context: Context<AcceptProtocolAdminAccountConstraints>,
     ) -> Result<()> {
-        handlers::ticketfair_ticket::buy_ticket(context, offchain_ref)
+        handlers::ticketfair_protocol::accept_protocol_admin(context)
     }
 
     /// Create a new Ticketfair user account.
@@ -78,34 +147,175 @@ pub mod escrow {
         handlers::ticketfair_event::activate_event(context)
     }
 
-    /// Finalize a Ticketfair auction and set the closing price.
+    /// Finalize a Ticketfair auction, setting each tier's closing price to its lowest awarded bid.
     pub fn finalize_auction(
         context: Context<FinalizeEventAccountConstraints>,
-        close_price: u64,
     ) -> Result<()> {
-        handlers::ticketfair_event::finalize_auction(context, close_price)
+        handlers::ticketfair_event::finalize_auction(context)
+    }
+
+    /// Cancel a Ticketfair event, making every escrowed bid and fixed-price ticket fully refundable.
+    pub fn cancel_event(
+        context: Context<CancelEventAccountConstraints>,
+    ) -> Result<()> {
+        handlers::ticketfair_event::cancel_event(context)
+    }
+
+    /// Withdraw the organizer's auction proceeds from escrow after finalization, paying the platform fee to the treasury.
+    pub fn withdraw_proceeds(
+        context: Context<WithdrawProceedsAccountConstraints>,
+    ) -> Result<()> {
+        handlers::ticketfair_event::withdraw_proceeds(context)
+    }
+
+    /// Set who may check tickets in for an event, and the check-in window around the event start.
+    pub fn set_gate_authority(
+        context: Context<SetGateAuthorityAccountConstraints>,
+        gate_authority: Pubkey,
+        check_in_opens_before: i64,
+        check_in_closes_after: i64,
+    ) -> Result<()> {
+        handlers::ticketfair_event::set_gate_authority(context, gate_authority, check_in_opens_before, check_in_closes_after)
+    }
+
+    /// Set the resale price cap and organizer royalty for an event, both in basis points.
+    pub fn set_resale_terms(
+        context: Context<SetResaleTermsAccountConstraints>,
+        resale_cap_bps: u16,
+        resale_royalty_bps: u16,
+    ) -> Result<()> {
+        handlers::ticketfair_event::set_resale_terms(context, resale_cap_bps, resale_royalty_bps)
     }
 
-    /// Place a bid for a ticket in a Ticketfair Dutch auction.
+    /// Require every bid on an event to carry an ed25519 attestation from `attestation_issuer`, or stop requiring it.
+    pub fn set_attestation_issuer(
+        context: Context<SetAttestationIssuerAccountConstraints>,
+        attestation_issuer: Option<Pubkey>,
+    ) -> Result<()> {
+        handlers::ticketfair_event::set_attestation_issuer(context, attestation_issuer)
+    }
+
+    /// Run the event as a Dutch or a sealed-bid auction, before it is activated.
+    /// A sealed-bid auction also needs the event's bid ranking, which the organizer pays for.
+    pub fn set_auction_type(
+        context: Context<SetAuctionTypeAccountConstraints>,
+        auction_type: AuctionType,
+    ) -> Result<()> {
+        handlers::ticketfair_event::set_auction_type(context, auction_type)
+    }
+
+    /// Set an allowlist presale before the auction starts, with an optional discount off the start price down to the end price.
+    pub fn set_presale(
+        context: Context<SetPresaleAccountConstraints>,
+        allowlist_root: [u8; 32],
+        presale_start_time: i64,
+        presale_discount_bps: u16,
+    ) -> Result<()> {
+        handlers::ticketfair_event::set_presale(context, allowlist_root, presale_start_time, presale_discount_bps)
+    }
+
+    /// Place a bid for `quantity` tickets of `tier` in a Ticketfair Dutch auction, paying the tier's current price up to `max_price` for each.
+    /// During the presale, `allowlist_proof` must prove the bidder is on the event's allowlist; presale tickets keep their fixed price.
+    /// Events with an attestation issuer need its ed25519 signature instruction right before this one.
     pub fn place_bid(
         context: Context<PlaceBidAccountConstraints>,
-        amount: u64,
+        tier: u8,
+        max_price: u64,
+        quantity: u32,
+        allowlist_proof: Vec<[u8; 32]>,
     ) -> Result<()> {
-        handlers::ticketfair_bid::place_bid(context, amount)
+        handlers::ticketfair_bid::place_bid(context, tier, max_price, quantity, allowlist_proof)
     }
 
-    /// Award a ticket to a bid in a Ticketfair Dutch auction.
-    pub fn award_ticket(
-        context: Context<AwardTicketAccountConstraints>,
+    /// Commit a sealed bid for `quantity` tickets of `tier`: `commitment` is sha256(price || salt || bidder),
+    /// and `deposit` must cover the price of every ticket, as it is escrowed until the auction settles.
+    pub fn commit_bid(
+        context: Context<CommitBidAccountConstraints>,
+        tier: u8,
+        commitment: [u8; 32],
+        quantity: u32,
+        deposit: u64,
+    ) -> Result<()> {
+        handlers::ticketfair_bid::commit_bid(context, tier, commitment, quantity, deposit)
+    }
+
+    /// Reveal a sealed bid's price per ticket after bidding ends, before the reveal deadline, ranking it in its tier.
+    pub fn reveal_bid(
+        context: Context<RevealBidAccountConstraints>,
+        price: u64,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        handlers::ticketfair_bid::reveal_bid(context, price, salt)
+    }
+
+    /// Award a ticket of `tier` to a bid for that tier in a Ticketfair auction; sealed bids only if their tier's ranking has them winning.
+    /// With the `bubblegum` feature, the cNFT Merkle proof is passed in remaining accounts.
+    pub fn award_ticket<'info>(
+        context: Context<'_, '_, 'info, 'info, AwardTicketAccountConstraints<'info>>,
+        tier: u8,
         cnft_asset_id: Pubkey,
+        transfer_args: CnftTransferArgs,
+    ) -> Result<()> {
+        handlers::ticketfair_bid::award_ticket(context, tier, cnft_asset_id, transfer_args)
+    }
+
+    /// Refund what a bid for `tier` is still owed in a Ticketfair Dutch auction, closing the bid once it is fully settled.
+    /// For an awarded bid of a cancelled event, the bid's tickets are passed in remaining accounts.
+    pub fn refund_bid<'info>(
+        context: Context<'_, '_, 'info, 'info, RefundBidAccountConstraints<'info>>,
+        tier: u8,
+    ) -> Result<()> {
+        handlers::ticketfair_bid::refund_bid(context, tier)
+    }
+
+    /// Close a bid nothing more can be owed on, returning its rent to the bidder.
+    pub fn close_bid(
+        context: Context<CloseBidAccountConstraints>,
+    ) -> Result<()> {
+        handlers::ticketfair_bid::close_bid(context)
+    }
+
+    /// Check a ticket in at the venue gate, marking it as used.
+    pub fn check_in_ticket(
+        context: Context<CheckInTicketAccountConstraints>,
+    ) -> Result<()> {
+        handlers::ticketfair_ticket::check_in_ticket(context)
+    }
+
+    /// List an owned ticket for resale, at most the organizer's price cap.
+    /// With the `bubblegum` feature, the cNFT Merkle proof is passed in remaining accounts.
+    pub fn list_ticket<'info>(
+        context: Context<'_, '_, 'info, 'info, ListTicketAccountConstraints<'info>>,
+        price: u64,
+        transfer_args: CnftTransferArgs,
+    ) -> Result<()> {
+        handlers::ticketfair_ticket::list_ticket(context, price, transfer_args)
+    }
+
+    /// Take a listed ticket off the resale market.
+    /// With the `bubblegum` feature, the cNFT Merkle proof is passed in remaining accounts.
+    pub fn delist_ticket<'info>(
+        context: Context<'_, '_, 'info, 'info, DelistTicketAccountConstraints<'info>>,
+        transfer_args: CnftTransferArgs,
+    ) -> Result<()> {
+        handlers::ticketfair_ticket::delist_ticket(context, transfer_args)
+    }
+
+    /// Buy a listed ticket, paying the organizer's royalty and moving the ticket to the buyer.
+    /// With the `bubblegum` feature, the cNFT Merkle proof is passed in remaining accounts.
+    pub fn buy_listed_ticket<'info>(
+        context: Context<'_, '_, 'info, 'info, BuyListedTicketAccountConstraints<'info>>,
+        ticket_index: u32,
+        transfer_args: CnftTransferArgs,
     ) -> Result<()> {
-        handlers::ticketfair_bid::award_ticket(context, cnft_asset_id)
+        handlers::ticketfair_ticket::buy_listed_ticket(context, ticket_index, transfer_args)
     }
 
-    /// Refund a bid in a Ticketfair Dutch auction.
-    pub fn refund_bid(
-        context: Context<RefundBidAccountConstraints>,
+    /// Close a refunded ticket, a used one once the event can no longer be cancelled, or an unused one once the event is over,
+    /// returning its rent to whoever paid it.
+    pub fn close_ticket(
+        context: Context<CloseTicketAccountConstraints>,
     ) -> Result<()> {
-        handlers::ticketfair_bid::refund_bid(context)
+        handlers::ticketfair_ticket::close_ticket(context)
     }
-}
\ No newline at end of file
+}
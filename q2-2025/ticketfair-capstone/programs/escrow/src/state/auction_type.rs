@@ -0,0 +1,19 @@
+//! Ticketfair auction types: the Dutch auction, or a sealed-bid (commit-reveal) auction
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+/// How an event's tickets are auctioned
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AuctionType {
+    /// Bids pay the current price as it falls along the event's price curve
+    Dutch,
+    /// Bids commit to a hidden price with a deposit until `auction_end_time`, then reveal it until `reveal_end_time`
+    SealedBid { reveal_end_time: i64 },
+}
+
+/// Commitment a sealed bid is placed with: sha256(price || salt || bidder).
+/// The bidder is part of it, so nobody can copy another wallet's commitment and reveal it as their own.
+pub fn sealed_bid_commitment(price: u64, salt: &[u8; 32], bidder: &Pubkey) -> [u8; 32] {
+    hashv(&[&price.to_le_bytes(), salt, bidder.as_ref()]).to_bytes()
+}
@@ -1,29 +1,57 @@
 //! Ticketfair Bid account definition (Dutch Auction)
 
 use anchor_lang::prelude::*;
-use crate::constants::*;
+use crate::state::BidStatus;
 
 #[account]
 pub struct Bid {
     pub bidder: Pubkey,
     pub event: Pubkey,
-    pub amount: u64,
-    pub status: u8, // Use constants: BID_STATUS_PENDING, BID_STATUS_AWARDED, BID_STATUS_REFUNDED
+    pub tier: u8, // Ticket tier bid for, part of the PDA seeds so a wallet can bid in several tiers
+    pub amount: u64, // Total escrowed for all tickets in the bid
+    pub quantity: u32, // Number of tickets bid for
+    pub tickets_awarded: u32, // Number of those tickets awarded so far
+    pub refunded_amount: u64, // Total refunded to the bidder so far
+    pub settled: bool, // Whether the bid has been paid everything it is owed and nothing more can change
+    pub commitment: [u8; 32], // Sealed bids: sha256(price || salt || bidder), zero for Dutch bids
+    pub revealed_price: u64, // Sealed bids: price per ticket once revealed, 0 until then
+    pub presale: bool, // Placed in the allowlist presale: its tickets cost the fixed presale price, not the close price
+    pub status: BidStatus,
     pub bump: u8,
 }
 
 impl Bid {
-    pub const INIT_SPACE: usize = 32 + 32 + 8 + 1 + 1;
+    pub const INIT_SPACE: usize = 32 + 32 + 1 + 8 + 4 + 4 + 8 + 1 + 32 + 8 + 1 + 1 + 1;
 
     /// Check if the bid can be refunded
     pub fn can_refund(&self) -> bool {
-        // Only allow refund if not already refunded
-        self.status != BID_STATUS_REFUNDED
+        // Only allow refund if not already refunded or settled
+        self.status != BidStatus::Refunded && !self.settled
     }
 
     /// Check if the bid can be awarded a ticket
     pub fn can_award(&self) -> bool {
-        // Only pending bids can be awarded
-        self.status == BID_STATUS_PENDING
+        // Refunded bids cannot be awarded, and a bid never gets more tickets than it asked for
+        self.status != BidStatus::Refunded && self.tickets_awarded < self.quantity
+    }
+
+    /// Price bid per ticket: the revealed price of a sealed bid, or the escrowed price of a Dutch one
+    pub fn unit_price(&self) -> Option<u64> {
+        if self.revealed_price > 0 {
+            return Some(self.revealed_price);
+        }
+        self.amount.checked_div(self.quantity as u64)
+    }
+
+    /// Amount owed back once the auction closes: the escrow minus the awarded tickets at the close price,
+    /// or at the bid's own fixed price for a presale bid
+    pub fn refund_due(&self, close_price: u64) -> Option<u64> {
+        let price = if self.presale { self.unit_price()? } else { close_price };
+        self.amount.checked_sub((self.tickets_awarded as u64).checked_mul(price)?)
+    }
+
+    /// Part of `refund_due` not paid out yet, so repeated refunds never pay the same amount twice
+    pub fn refund_outstanding(&self, refund_due: u64) -> Option<u64> {
+        refund_due.checked_sub(self.refunded_amount)
     }
 }
\ No newline at end of file
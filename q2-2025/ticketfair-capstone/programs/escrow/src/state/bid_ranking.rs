@@ -0,0 +1,88 @@
+//! Ticketfair BidRanking account definition (the revealed sealed bids that win each tier)
+
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use crate::state::TicketTier;
+
+#[account]
+pub struct BidRanking {
+    /// The sealed-bid event being ranked
+    pub event: Pubkey,
+    /// One ranking per ticket tier, in the event's tier order
+    pub tiers: Vec<TierRanking>,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl BidRanking {
+    /// Space for the ranking of every ticket in `tiers`
+    pub fn space(tiers: &[TicketTier]) -> usize {
+        32 + // event
+        4 + tiers.iter().map(|tier| TierRanking::space(tier.ticket_supply)).sum::<usize>() + // tiers vector
+        1 // bump
+    }
+
+    /// The ranking of the ticket tier at `tier`, to update
+    pub fn tier_mut(&mut self, tier: u8) -> Result<&mut TierRanking> {
+        self.tiers.get_mut(tier as usize).ok_or(error!(ErrorCode::InvalidTier))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TierRanking {
+    /// Per-ticket prices of the revealed bids that would win the tier, highest first, at most its ticket supply
+    pub winning_prices: Vec<u64>,
+    /// Tickets awarded at the cutoff (lowest winning) price, which only get the seats the higher bids leave over
+    pub cutoff_tickets_awarded: u32,
+}
+
+impl TierRanking {
+    /// Space for a tier of `ticket_supply` tickets
+    pub fn space(ticket_supply: u32) -> usize {
+        4 + 8 * ticket_supply as usize + // winning_prices vector
+        4 // cutoff_tickets_awarded
+    }
+
+    /// Record a revealed bid for `quantity` tickets at `price`, keeping only the `ticket_supply` highest-priced tickets.
+    /// Tickets tied with ones revealed earlier rank after them.
+    pub fn record_revealed_bid(&mut self, price: u64, quantity: u32, ticket_supply: u32) {
+        let position = self.winning_prices.partition_point(|&winning_price| winning_price >= price);
+        if position >= ticket_supply as usize {
+            return;
+        }
+        let tickets = quantity.min(ticket_supply - position as u32) as usize;
+        self.winning_prices.splice(position..position, std::iter::repeat_n(price, tickets));
+        self.winning_prices.truncate(ticket_supply as usize);
+    }
+
+    /// Lowest price that still wins one of the tier's tickets, once more tickets were revealed than the tier holds
+    pub fn cutoff_price(&self, ticket_supply: u32) -> Option<u64> {
+        if self.winning_prices.len() < ticket_supply as usize {
+            return None;
+        }
+        self.winning_prices.last().copied()
+    }
+
+    /// Check if a revealed bid at `price` can be awarded a ticket: fewer than the tier's supply of revealed tickets beat it,
+    /// and bids tied at the cutoff price only share the seats left by higher bids, so those can always be awarded in full
+    pub fn can_award(&self, price: u64, ticket_supply: u32) -> bool {
+        match self.cutoff_price(ticket_supply) {
+            // Fewer tickets revealed than the tier holds: every revealed bid wins
+            None => true,
+            Some(cutoff) if price > cutoff => true,
+            Some(cutoff) if price == cutoff => {
+                let seats_above = self.winning_prices.iter().filter(|&&winning_price| winning_price > cutoff).count();
+                (self.cutoff_tickets_awarded as usize) < self.winning_prices.len() - seats_above
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// Record a ticket awarded to a revealed bid at `price`
+    pub fn record_award(&mut self, price: u64, ticket_supply: u32) -> Option<()> {
+        if self.cutoff_price(ticket_supply) == Some(price) {
+            self.cutoff_tickets_awarded = self.cutoff_tickets_awarded.checked_add(1)?;
+        }
+        Some(())
+    }
+}
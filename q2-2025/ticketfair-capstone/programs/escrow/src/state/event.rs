@@ -1,78 +1,299 @@
 //! Ticketfair Event account definition (Dutch Auction)
 
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use std::ops::Range;
 use crate::constants::*;
+use crate::error::ErrorCode;
+use crate::state::{AuctionType, EventStatus, TicketTier, BPS_DENOMINATOR};
 
 #[account]
 pub struct Event {
     /// The event organizer
     pub organizer: Pubkey,
+    /// Per-organizer event number, taken from the organizer's `User::events_created` counter
+    pub event_id: u32,
     /// Off-chain metadata reference (e.g., Walrus blob URL)
     pub metadata_url: String,
-    /// Total number of tickets available
+    /// Total number of tickets available, across every tier
     pub ticket_supply: u32,
-    /// Number of tickets awarded so far
+    /// Number of tickets awarded so far, across every tier
     pub tickets_awarded: u32,
-    /// Starting price for Dutch auction (in lamports)
-    pub start_price: u64,
-    /// Ending price for Dutch auction (in lamports)
-    pub end_price: u64,
+    /// Most tickets a single wallet can bid for or hold in each tier
+    pub max_tickets_per_wallet: u32,
+    /// Ticket tiers, each with its own supply, prices and cNFT metadata (at most `MAX_TIERS_PER_EVENT`).
+    /// Tickets are numbered and minted tier by tier, in this order.
+    pub tiers: Vec<TicketTier>,
+    /// Dutch or sealed-bid auction, the same for every tier; for sealed bids a tier's `end_price` is its reserve price
+    pub auction_type: AuctionType,
+    /// Allowlist presale start time, before `auction_start_time` (0 if there is no presale)
+    pub presale_start_time: i64,
+    /// Merkle root of the wallets allowed to bid during the presale
+    pub allowlist_root: [u8; 32],
+    /// Presale discount off each tier's `start_price`, in basis points.
+    /// Awarded presale bids pay their own fixed price and stay out of the public clearing price.
+    pub presale_discount_bps: u16,
+    /// Off-chain issuer whose ed25519 attestation every bid needs (None to allow any bidder)
+    pub attestation_issuer: Option<Pubkey>,
     /// Auction start time (Unix timestamp)
     pub auction_start_time: i64,
     /// Auction end time (Unix timestamp)
     pub auction_end_time: i64,
-    /// The price at which the auction closed (set when auction ends, 0 if not finalized)
-    pub auction_close_price: u64,
-    /// Current status (0 = Created, 1 = Active, 2 = Finalized, 3 = Cancelled)
-    pub status: u8,
+    /// Event (show) start time (Unix timestamp), after the auction ends
+    pub event_start_time: i64,
+    /// Key allowed to check tickets in at the venue (the organizer unless delegated)
+    pub gate_authority: Pubkey,
+    /// Seconds before `event_start_time` that check-in opens
+    pub check_in_opens_before: i64,
+    /// Seconds after `event_start_time` that check-in closes
+    pub check_in_closes_after: i64,
+    /// Highest resale price, in basis points of the ticket's tier close price
+    pub resale_cap_bps: u16,
+    /// Share of every resale paid to the organizer, in basis points
+    pub resale_royalty_bps: u16,
+    /// Platform fee on the event's proceeds, in basis points, taken from the protocol config when the event is created
+    pub protocol_fee_bps: u16,
+    /// Total lamports escrowed by bids
+    pub total_escrowed: u64,
+    /// Total lamports refunded to bidders from escrow
+    pub total_refunded: u64,
+    /// Total lamports of proceeds already withdrawn by the organizer
+    pub proceeds_withdrawn: u64,
+    /// Current status, changed only through `transition`
+    pub status: EventStatus,
     /// PDA bump
     pub bump: u8,
+    /// SPL Token / Token-2022 mint bids are paid in (None for native SOL)
+    pub payment_mint: Option<Pubkey>,
     /// Bubblegum Merkle Tree address for cNFTs
     pub merkle_tree: Pubkey,
-    /// Asset IDs of cNFTs minted for this event (max 1000 tickets)
-    pub cnft_asset_ids: Vec<Pubkey>, // #[max_len = 1000]
+    /// Number of cNFTs minted so far, kept in `TicketInventory` pages of `TICKETS_PER_INVENTORY_PAGE`
+    pub tickets_minted: u32,
 }
 
 impl Event {
     pub const INIT_SPACE: usize = 32 + // organizer pubkey
+                               4 + // event_id
                                4 + MAX_METADATA_URL_LEN + // metadata_url string
                                4 + // ticket_supply
                                4 + // tickets_awarded
-                               8 + // start_price
-                               8 + // end_price
+                               4 + // max_tickets_per_wallet
+                               4 + (TicketTier::INIT_SPACE * MAX_TIERS_PER_EVENT) + // tiers vector
+                               AuctionType::INIT_SPACE + // auction_type
+                               8 + // presale_start_time
+                               32 + // allowlist_root
+                               2 + // presale_discount_bps
+                               1 + 32 + // attestation_issuer
                                8 + // auction_start_time
                                8 + // auction_end_time
-                               8 + // auction_close_price
+                               8 + // event_start_time
+                               32 + // gate_authority
+                               8 + // check_in_opens_before
+                               8 + // check_in_closes_after
+                               2 + // resale_cap_bps
+                               2 + // resale_royalty_bps
+                               2 + // protocol_fee_bps
+                               8 + // total_escrowed
+                               8 + // total_refunded
+                               8 + // proceeds_withdrawn
                                1 + // status 
                                1 + // bump
+                               1 + 32 + // payment_mint
                                32 + // merkle_tree
-                               4 + (32 * MAX_TICKETS_TEST_MODE as usize); // cnft_asset_ids vector
-
-    /// Calculate the current auction price based on the event parameters and the given timestamp.
-    pub fn get_current_auction_price(&self, now: i64) -> u64 {
-        if now <= self.auction_start_time {
-            self.start_price
-        } else if now >= self.auction_end_time {
-            self.end_price
-        } else {
-            let elapsed = now - self.auction_start_time;
-            let duration = self.auction_end_time - self.auction_start_time;
-            let price_diff = self.start_price.saturating_sub(self.end_price);
-            self.start_price - ((price_diff as i64 * elapsed) / duration) as u64
+                               4; // tickets_minted
+
+    /// The ticket tier at `tier`
+    pub fn tier(&self, tier: u8) -> Result<&TicketTier> {
+        self.tiers.get(tier as usize).ok_or(error!(ErrorCode::InvalidTier))
+    }
+
+    /// The ticket tier at `tier`, to update
+    pub fn tier_mut(&mut self, tier: u8) -> Result<&mut TicketTier> {
+        self.tiers.get_mut(tier as usize).ok_or(error!(ErrorCode::InvalidTier))
+    }
+
+    /// Event-wide numbers of the tickets in `tier`, which follow the tickets of every tier before it
+    pub fn tier_tickets(&self, tier: u8) -> Result<Range<u32>> {
+        let supply = self.tier(tier)?.ticket_supply;
+        let start: u32 = self.tiers[..tier as usize].iter().map(|tier| tier.ticket_supply).sum();
+        Ok(start..start + supply)
+    }
+
+    /// The tier that event-wide ticket number `ticket` belongs to
+    pub fn tier_of_ticket(&self, ticket: u32) -> Option<u8> {
+        let mut end = 0u32;
+        for (index, tier) in self.tiers.iter().enumerate() {
+            end = end.saturating_add(tier.ticket_supply);
+            if ticket < end {
+                return Some(index as u8);
+            }
+        }
+        None
+    }
+
+    /// Calculate the current auction price of `tier` based on its price curve and the given timestamp.
+    pub fn get_current_auction_price(&self, tier: u8, now: i64) -> Result<u64> {
+        let tier = self.tier(tier)?;
+        Ok(tier.price_curve.price_at(
+            tier.start_price,
+            tier.end_price,
+            self.auction_start_time,
+            self.auction_end_time,
+            now,
+        ))
+    }
+
+    /// Check if the event runs a sealed-bid auction
+    pub fn is_sealed_bid(&self) -> bool {
+        matches!(self.auction_type, AuctionType::SealedBid { .. })
+    }
+
+    /// When bidding is over for good: after the reveals of a sealed-bid auction, or at the end of a Dutch one
+    pub fn auction_close_time(&self) -> i64 {
+        match self.auction_type {
+            AuctionType::Dutch => self.auction_end_time,
+            AuctionType::SealedBid { reveal_end_time } => reveal_end_time,
         }
     }
 
-    /// Check if the auction is within the valid time window for bidding
-    pub fn is_active_for_bidding(&self, now: i64) -> bool {
-        self.status == EVENT_STATUS_ACTIVE && 
-        now >= self.auction_start_time && 
-        now <= self.auction_end_time
+    /// Check if sealed bids can be revealed at the given time: after bidding ends, until the reveal deadline
+    pub fn is_reveal_open(&self, now: i64) -> bool {
+        self.is_sealed_bid() && now > self.auction_end_time && now <= self.auction_close_time()
+    }
+
+    /// Count a ticket of `tier` as awarded or sold, in the tier and across the event
+    pub fn record_awarded_ticket(&mut self, tier: u8) -> Result<()> {
+        let tier = self.tier_mut(tier)?;
+        if tier.is_sold_out() {
+            return Err(error!(ErrorCode::TicketsSoldOut));
+        }
+        tier.tickets_awarded += 1;
+        self.tickets_awarded = self.tickets_awarded.checked_add(1).ok_or(error!(ErrorCode::CustomError))?;
+        Ok(())
+    }
+
+    /// Check if every ticket has been minted into the event's inventory
+    pub fn is_inventory_complete(&self) -> bool {
+        self.tickets_minted == self.ticket_supply
+    }
+
+    /// The inventory page the next minted ticket goes onto
+    pub fn next_inventory_page(&self) -> u32 {
+        self.tickets_minted / TICKETS_PER_INVENTORY_PAGE
+    }
+
+    /// Check if a wallet may bid for, or hold, `quantity` tickets of one of this event's tiers
+    pub fn is_within_wallet_limit(&self, quantity: u32) -> bool {
+        quantity > 0 && quantity <= self.max_tickets_per_wallet
+    }
+
+    /// Check if the allowlist presale is running: an active event between the presale start and the auction start
+    pub fn is_presale_open(&self, now: i64) -> bool {
+        self.status == EventStatus::Active &&
+        self.presale_start_time != 0 &&
+        now >= self.presale_start_time &&
+        now < self.auction_start_time
+    }
+
+    /// Fixed presale price of `tier`: its `start_price` less the presale discount
+    pub fn presale_price(&self, tier: u8) -> Result<u64> {
+        Ok(self.tier(tier)?.presale_price(self.presale_discount_bps))
     }
 
-    /// Check if the auction is in a valid state for finalizing (setting close price)
+    /// Check a Merkle proof that `wallet` is on the presale allowlist.
+    /// Leaves are `sha256(wallet)`, and each pair of nodes is hashed in sorted order.
+    pub fn is_on_allowlist(&self, wallet: &Pubkey, proof: &[[u8; 32]]) -> bool {
+        let mut node = hashv(&[wallet.as_ref()]).to_bytes();
+        for sibling in proof {
+            node = if node <= *sibling {
+                hashv(&[&node, sibling]).to_bytes()
+            } else {
+                hashv(&[sibling, &node]).to_bytes()
+            };
+        }
+        node == self.allowlist_root
+    }
+
+    /// Check if the auction is in a valid state for finalizing (setting close prices), after any sealed bid reveals
     pub fn can_finalize(&self, now: i64) -> bool {
-        self.status == EVENT_STATUS_ACTIVE && 
-        now >= self.auction_end_time &&
-        self.auction_close_price == 0
+        self.status == EventStatus::Active && 
+        now >= self.auction_close_time() &&
+        self.tiers.iter().all(|tier| tier.close_price == 0)
+    }
+
+    /// Total proceeds owed to the organizer: every awarded ticket at its tier's closing price
+    pub fn total_proceeds(&self) -> Option<u64> {
+        self.tiers
+            .iter()
+            .try_fold(0u64, |total, tier| total.checked_add(tier.proceeds()?))
+    }
+
+    /// Lamports the escrow must keep to cover every refund still owed to bidders
+    pub fn outstanding_refunds(&self) -> Option<u64> {
+        self.total_escrowed
+            .checked_sub(self.total_refunded)?
+            .checked_sub(self.total_proceeds()?)
+    }
+
+    /// Check the escrow can pay out `amount` of proceeds and still hold every refund owed, on top of the
+    /// `rent_reserve` it keeps to stay rent-exempt
+    pub fn escrow_covers_withdrawal(&self, amount: u64, escrow_balance: u64, rent_reserve: u64) -> bool {
+        let required_balance = self
+            .outstanding_refunds()
+            .and_then(|outstanding| outstanding.checked_add(amount))
+            .and_then(|required| required.checked_add(rent_reserve));
+        matches!(required_balance, Some(required) if escrow_balance >= required)
+    }
+
+    /// Check if the organizer can cancel the event: before finalization, or after it until check-in opens
+    pub fn can_cancel(&self, now: i64) -> bool {
+        match self.status {
+            EventStatus::Created | EventStatus::Active => true,
+            // Once proceeds are withdrawn the escrow can no longer cover full refunds,
+            // and once check-in opens a checked-in ticket could no longer be refunded
+            EventStatus::Finalized => now < self.check_in_opens_at() && self.proceeds_withdrawn == 0,
+            _ => false,
+        }
+    }
+
+    /// Check if a finalized event is over at the given time: its check-in window has closed
+    pub fn has_ended(&self, now: i64) -> bool {
+        self.status == EventStatus::Finalized
+            && now > self.event_start_time.saturating_add(self.check_in_closes_after)
+    }
+
+    /// When check-in opens, `check_in_opens_before` ahead of the event start
+    pub fn check_in_opens_at(&self) -> i64 {
+        self.event_start_time.saturating_sub(self.check_in_opens_before)
+    }
+
+    /// Check if tickets can be checked in at the given time: a finalized event inside its check-in window
+    pub fn is_check_in_open(&self, now: i64) -> bool {
+        let closes = self.event_start_time.saturating_add(self.check_in_closes_after);
+        self.status == EventStatus::Finalized && now >= self.check_in_opens_at() && now <= closes
+    }
+
+    /// Check if tickets can be listed or bought on resale: a finalized event that has not started yet
+    /// and can no longer be cancelled, since a cancelled event only refunds the tickets' original holders
+    pub fn is_resale_open(&self, now: i64) -> bool {
+        self.status == EventStatus::Finalized && now < self.event_start_time && !self.can_cancel(now)
+    }
+
+    /// Highest price a ticket of `tier` can be resold for: `resale_cap_bps` of the tier's close price
+    pub fn max_resale_price(&self, tier: u8) -> Result<u64> {
+        let cap = (self.tier(tier)?.close_price as u128) * (self.resale_cap_bps as u128) / BPS_DENOMINATOR;
+        u64::try_from(cap).map_err(|_| error!(ErrorCode::CustomError))
+    }
+
+    /// Platform fee owed to the protocol treasury on `amount` of proceeds
+    pub fn protocol_fee(&self, amount: u64) -> Option<u64> {
+        let fee = (amount as u128).checked_mul(self.protocol_fee_bps as u128)? / BPS_DENOMINATOR;
+        u64::try_from(fee).ok()
+    }
+
+    /// Organizer royalty owed on a resale at `price`
+    pub fn resale_royalty(&self, price: u64) -> Option<u64> {
+        let royalty = (price as u128).checked_mul(self.resale_royalty_bps as u128)? / BPS_DENOMINATOR;
+        u64::try_from(royalty).ok()
     }
 }
\ No newline at end of file
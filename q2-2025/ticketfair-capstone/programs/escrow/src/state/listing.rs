@@ -0,0 +1,16 @@
+//! Ticketfair Listing account definition (secondary resale)
+
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct Listing {
+    pub seller: Pubkey,
+    pub event: Pubkey,
+    pub ticket: Pubkey,
+    pub price: u64, // Asking price, in the event's payment currency
+    pub bump: u8,
+}
+
+impl Listing {
+    pub const INIT_SPACE: usize = 32 + 32 + 32 + 8 + 1;
+}
@@ -3,9 +3,25 @@ pub mod event;
 pub mod ticket;
 pub mod user;
 pub mod bid;
+pub mod price_curve;
+pub mod listing;
+pub mod status;
+pub mod ticket_inventory;
+pub mod auction_type;
+pub mod ticket_tier;
+pub mod protocol_config;
+pub mod bid_ranking;
 
 pub use offer::*;
 pub use event::*;
 pub use ticket::*;
 pub use user::*;
 pub use bid::*;
+pub use price_curve::*;
+pub use listing::*;
+pub use status::*;
+pub use ticket_inventory::*;
+pub use auction_type::*;
+pub use ticket_tier::*;
+pub use protocol_config::*;
+pub use bid_ranking::*;
@@ -0,0 +1,79 @@
+//! Ticketfair price curves for the Dutch auction
+
+use anchor_lang::prelude::*;
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+/// How the auction price falls from `start_price` to `end_price` over the auction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum PriceCurve {
+    /// Straight line from the start price to the end price
+    Linear,
+    /// The amount above the end price halves every `half_life` seconds
+    ExponentialDecay { half_life: i64 },
+    /// The price drops by `drop_bps` of the start price every `step_interval` seconds
+    Stepwise { step_interval: i64, drop_bps: u16 },
+}
+
+impl PriceCurve {
+    /// Check the curve parameters are usable
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            PriceCurve::Linear => true,
+            PriceCurve::ExponentialDecay { half_life } => half_life > 0,
+            PriceCurve::Stepwise { step_interval, drop_bps } => {
+                step_interval > 0 && drop_bps > 0 && drop_bps as u128 <= BPS_DENOMINATOR
+            }
+        }
+    }
+
+    /// Price at `now` for an auction running from `start_time` to `end_time`, never below `end_price`.
+    /// All intermediate math is done in u128, so no input can overflow.
+    pub fn price_at(
+        &self,
+        start_price: u64,
+        end_price: u64,
+        start_time: i64,
+        end_time: i64,
+        now: i64,
+    ) -> u64 {
+        if now <= start_time {
+            return start_price;
+        }
+        if now >= end_time {
+            return end_price;
+        }
+
+        let elapsed = (now as i128 - start_time as i128) as u128;
+        let duration = (end_time as i128 - start_time as i128) as u128;
+        let start = start_price as u128;
+        let end = end_price as u128;
+        let price_diff = start.saturating_sub(end);
+
+        let price = match *self {
+            PriceCurve::Linear => start - price_diff * elapsed / duration,
+            PriceCurve::ExponentialDecay { half_life } => {
+                let half_life = half_life.max(1) as u128;
+                let halvings = elapsed / half_life;
+                let remainder = elapsed % half_life;
+                // Whole half-lives halve the amount above the end price
+                let above_end = if halvings >= 128 { 0 } else { price_diff >> halvings };
+                // Within a half-life, interpolate linearly from 100% down to 50%
+                let above_end = above_end - above_end * remainder / (2 * half_life);
+                end + above_end
+            }
+            PriceCurve::Stepwise { step_interval, drop_bps } => {
+                let steps = elapsed / step_interval.max(1) as u128;
+                let drop = start
+                    .saturating_mul(drop_bps as u128)
+                    .saturating_mul(steps)
+                    / BPS_DENOMINATOR;
+                start.saturating_sub(drop)
+            }
+        };
+
+        // Never below the end price; the result is between end and start, so it fits in u64
+        price.max(end).min(start.max(end)) as u64
+    }
+}
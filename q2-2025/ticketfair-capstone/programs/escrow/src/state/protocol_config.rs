@@ -0,0 +1,95 @@
+//! Ticketfair ProtocolConfig account definition (program-wide admin, platform fee and limits)
+
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::error::ErrorCode;
+
+/// Limits every new event is checked against, overriding the compile-time defaults
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct ProtocolLimits {
+    /// Lowest start or end price of any tier (in lamports)
+    pub min_ticket_price: u64,
+    /// Shortest auction, in seconds
+    pub min_auction_duration: i64,
+    /// Longest auction, in seconds
+    pub max_auction_duration: i64,
+    /// Most tickets in an event, across every tier
+    pub max_tickets_per_event: u32,
+}
+
+impl Default for ProtocolLimits {
+    fn default() -> Self {
+        ProtocolLimits {
+            min_ticket_price: MIN_TICKET_PRICE,
+            min_auction_duration: MIN_AUCTION_DURATION,
+            max_auction_duration: MAX_AUCTION_DURATION,
+            max_tickets_per_event: MAX_TICKETS_PER_EVENT,
+        }
+    }
+}
+
+impl ProtocolLimits {
+    /// Check the limits are usable; events never get more tickets than the Merkle tree and inventory are sized for
+    pub fn is_valid(&self) -> bool {
+        self.min_ticket_price > 0
+            && self.min_auction_duration > 0
+            && self.min_auction_duration <= self.max_auction_duration
+            && self.max_tickets_per_event > 0
+            && self.max_tickets_per_event <= MAX_TICKETS_PER_EVENT
+    }
+
+    /// Check a tier's start or end price is at least the minimum ticket price
+    pub fn check_ticket_price(&self, price: u64) -> Result<()> {
+        if price < self.min_ticket_price {
+            return Err(error!(ErrorCode::TicketPriceBelowMinimum));
+        }
+        Ok(())
+    }
+
+    /// Check an event's ticket supply, across every tier, is within the maximum
+    pub fn check_ticket_supply(&self, ticket_supply: u32) -> Result<()> {
+        if ticket_supply > self.max_tickets_per_event {
+            return Err(error!(ErrorCode::TicketSupplyAboveMaximum));
+        }
+        Ok(())
+    }
+
+    /// Check an auction running from `start_time` to `end_time` is neither too short nor too long
+    pub fn check_auction(&self, start_time: i64, end_time: i64) -> Result<()> {
+        let duration = end_time.saturating_sub(start_time);
+        if !(self.min_auction_duration..=self.max_auction_duration).contains(&duration) {
+            return Err(error!(ErrorCode::AuctionDurationOutOfRange));
+        }
+        Ok(())
+    }
+}
+
+#[account]
+pub struct ProtocolConfig {
+    /// Key allowed to change the fee, treasury and limits
+    pub admin: Pubkey,
+    /// Proposed new admin, who takes over once they accept (None if no handover is under way)
+    pub pending_admin: Option<Pubkey>,
+    /// Platform fee on event proceeds, in basis points, fixed for each event when it is created
+    pub fee_bps: u16,
+    /// Wallet the platform fee is paid to
+    pub treasury: Pubkey,
+    /// Limits checked when events are created
+    pub limits: ProtocolLimits,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl ProtocolConfig {
+    pub const INIT_SPACE: usize = 32 + // admin
+                               1 + 32 + // pending_admin
+                               2 + // fee_bps
+                               32 + // treasury
+                               ProtocolLimits::INIT_SPACE + // limits
+                               1; // bump
+
+    /// Check a platform fee is within the protocol's cap
+    pub fn is_valid_fee(fee_bps: u16) -> bool {
+        fee_bps <= MAX_PROTOCOL_FEE_BPS
+    }
+}
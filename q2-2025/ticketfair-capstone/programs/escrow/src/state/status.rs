@@ -0,0 +1,86 @@
+//! Ticketfair status enums and the state machine that moves Event, Bid and Ticket accounts between them
+
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum EventStatus {
+    Created,
+    Active,
+    Finalized,
+    Cancelled,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum BidStatus {
+    Pending,
+    Awarded,
+    Refunded,
+    Revealed,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum TicketStatus {
+    Owned,
+    Claimed,
+    Refunded,
+    Listed,
+}
+
+/// A status with a fixed set of legal moves
+pub trait StatusTransition: Copy + PartialEq {
+    /// Error returned for a move the state machine does not allow
+    const TRANSITION_ERROR: ErrorCode;
+
+    /// Check if the state machine allows moving from `self` to `next`
+    fn can_transition_to(self, next: Self) -> bool;
+}
+
+impl StatusTransition for EventStatus {
+    const TRANSITION_ERROR: ErrorCode = ErrorCode::InvalidEventStatusTransition;
+
+    fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (EventStatus::Created, EventStatus::Active)
+                | (EventStatus::Active, EventStatus::Finalized)
+                | (EventStatus::Created | EventStatus::Active | EventStatus::Finalized, EventStatus::Cancelled)
+        )
+    }
+}
+
+impl StatusTransition for BidStatus {
+    const TRANSITION_ERROR: ErrorCode = ErrorCode::InvalidBidStatusTransition;
+
+    fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            // Sealed bids are revealed before they can be awarded
+            (BidStatus::Pending, BidStatus::Revealed)
+                // A bid for several tickets is awarded again for each one
+                | (BidStatus::Pending | BidStatus::Revealed | BidStatus::Awarded, BidStatus::Awarded)
+                | (BidStatus::Pending | BidStatus::Revealed | BidStatus::Awarded, BidStatus::Refunded)
+        )
+    }
+}
+
+impl StatusTransition for TicketStatus {
+    const TRANSITION_ERROR: ErrorCode = ErrorCode::InvalidTicketStatusTransition;
+
+    fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (TicketStatus::Owned, TicketStatus::Claimed | TicketStatus::Refunded | TicketStatus::Listed)
+                | (TicketStatus::Listed, TicketStatus::Owned)
+        )
+    }
+}
+
+/// Move `status` to `next`, the single place every handler changes a status through
+pub fn transition<S: StatusTransition>(status: &mut S, next: S) -> Result<()> {
+    if !status.can_transition_to(next) {
+        return Err(error!(S::TRANSITION_ERROR));
+    }
+    *status = next;
+    Ok(())
+}
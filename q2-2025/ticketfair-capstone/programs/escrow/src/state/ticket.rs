@@ -2,30 +2,64 @@
 
 use anchor_lang::prelude::*;
 use crate::constants::*;
+use crate::state::TicketStatus;
 
 #[account]
 pub struct Ticket {
     pub owner: Pubkey,
     pub event: Pubkey,
-    pub status: u8, // Use constants: TICKET_STATUS_OWNED, TICKET_STATUS_CLAIMED, TICKET_STATUS_REFUNDED
+    pub status: TicketStatus,
     pub offchain_ref: String, // Walrus blob or metadata URL
     pub bump: u8,
     /// The cNFT asset ID for this ticket (Bubblegum)
     pub cnft_asset_id: Pubkey,
+    /// Per-owner ticket number within the tier, part of the PDA seeds so a wallet can hold several tickets
+    pub index: u32,
+    /// Ticket tier, also part of the PDA seeds
+    pub tier: u8,
+    /// Who paid the rent for this account, and gets it back when the ticket is closed
+    pub rent_payer: Pubkey,
+    /// Price paid into escrow by a fixed-price sale, refunded if the event is cancelled
+    /// (0 for awarded tickets, which are refunded through their bid)
+    pub purchase_price: u64,
 }
 
 impl Ticket {
-    pub const INIT_SPACE: usize = 32 + 32 + 1 + 4 + MAX_METADATA_URL_LEN + 1 + 32;
+    pub const INIT_SPACE: usize = 32 + 32 + 1 + 4 + MAX_METADATA_URL_LEN + 1 + 32 + 4 + 1 + 32 + 8;
     
     /// Check if the ticket can be claimed
     pub fn can_claim(&self) -> bool {
         // Only owned tickets can be claimed
-        self.status == TICKET_STATUS_OWNED
+        self.status == TicketStatus::Owned
     }
     
     /// Check if the ticket can be refunded
     pub fn can_refund(&self) -> bool {
         // Only owned tickets can be refunded
-        self.status == TICKET_STATUS_OWNED
+        self.status == TicketStatus::Owned
+    }
+
+    /// Check if the ticket's own purchase price can be refunded, once its event is cancelled
+    pub fn can_refund_purchase(&self) -> bool {
+        // Awarded tickets have nothing of their own to refund, their bid covers them
+        self.purchase_price > 0 && self.can_refund()
+    }
+
+    /// Check if the ticket can be listed for resale
+    pub fn can_list(&self) -> bool {
+        // Only owned tickets can be listed, so used or refunded tickets never change hands
+        self.status == TicketStatus::Owned
+    }
+
+    /// Check if the ticket account can be closed, given whether its event is over and whether it can still be cancelled
+    pub fn can_close(&self, event_ended: bool, event_cancellable: bool) -> bool {
+        match self.status {
+            TicketStatus::Refunded => true,
+            // A cancellation would need every awarded ticket for the bid refund
+            TicketStatus::Claimed => !event_cancellable,
+            // Unused tickets stay until the event is over, listed ones until they are delisted
+            TicketStatus::Owned => event_ended,
+            TicketStatus::Listed => false,
+        }
     }
 }
\ No newline at end of file
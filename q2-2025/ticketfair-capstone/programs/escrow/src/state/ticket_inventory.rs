@@ -0,0 +1,75 @@
+//! Ticketfair TicketInventory account definition (one page of an event's cNFTs)
+
+use anchor_lang::prelude::*;
+use std::ops::Range;
+use crate::constants::*;
+use crate::error::ErrorCode;
+
+#[account]
+pub struct TicketInventory {
+    /// The event these tickets belong to
+    pub event: Pubkey,
+    /// Page number, covering tickets `page * TICKETS_PER_INVENTORY_PAGE` onwards
+    pub page: u32,
+    /// Asset IDs of cNFTs minted onto this page
+    pub asset_ids: Vec<Pubkey>, // #[max_len = TICKETS_PER_INVENTORY_PAGE]
+    /// Whether the cNFT at the same index in `asset_ids` has been awarded
+    pub awarded: Vec<bool>,
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl TicketInventory {
+    pub const INIT_SPACE: usize = 32 + // event
+                               4 + // page
+                               4 + (32 * TICKETS_PER_INVENTORY_PAGE as usize) + // asset_ids vector
+                               4 + TICKETS_PER_INVENTORY_PAGE as usize + // awarded vector
+                               1; // bump
+
+    /// Number of tickets that can still be minted onto this page
+    pub fn remaining_capacity(&self) -> u32 {
+        TICKETS_PER_INVENTORY_PAGE.saturating_sub(self.asset_ids.len() as u32)
+    }
+
+    /// Add a newly minted cNFT to this page
+    pub fn add_asset(&mut self, asset_id: Pubkey) -> Result<()> {
+        if self.remaining_capacity() == 0 {
+            return Err(error!(ErrorCode::TicketInventoryPageFull));
+        }
+        self.asset_ids.push(asset_id);
+        self.awarded.push(false);
+        Ok(())
+    }
+
+    /// Event-wide ticket number of the asset at `index` on this page
+    pub fn ticket_number(&self, index: usize) -> u32 {
+        self.page * TICKETS_PER_INVENTORY_PAGE + index as u32
+    }
+
+    /// Find the index of an asset in `asset_ids` that has not been awarded yet, among the event-wide ticket numbers in `tickets`
+    pub fn find_unawarded_asset(&self, asset_id: &Pubkey, tickets: &Range<u32>) -> Result<usize> {
+        let mut belongs_to_event = false;
+        let mut belongs_to_tier = false;
+        for (index, id) in self.asset_ids.iter().enumerate() {
+            if id != asset_id {
+                continue;
+            }
+            belongs_to_event = true;
+            if !tickets.contains(&self.ticket_number(index)) {
+                continue;
+            }
+            belongs_to_tier = true;
+            if !self.awarded.get(index).copied().unwrap_or(false) {
+                return Ok(index);
+            }
+        }
+
+        if belongs_to_tier {
+            Err(error!(ErrorCode::CnftAssetAlreadyAwarded))
+        } else if belongs_to_event {
+            Err(error!(ErrorCode::CnftAssetNotInTier))
+        } else {
+            Err(error!(ErrorCode::CnftAssetNotInEvent))
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Ticketfair ticket tiers, each sold in its own auction within the event
+
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::state::{PriceCurve, BPS_DENOMINATOR};
+
+/// What the organizer chooses for a tier when creating the event
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TicketTierArgs {
+    /// Metadata URI of the tier's cNFTs
+    pub metadata_url: String,
+    /// Number of tickets in the tier
+    pub ticket_supply: u32,
+    /// Starting price for the tier's Dutch auction (in lamports)
+    pub start_price: u64,
+    /// Ending price for the tier's Dutch auction, or its reserve price in a sealed-bid auction (in lamports)
+    pub end_price: u64,
+    /// How the price falls from `start_price` to `end_price`
+    pub price_curve: PriceCurve,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TicketTier {
+    /// Metadata URI of the tier's cNFTs
+    pub metadata_url: String,
+    /// Number of tickets in the tier
+    pub ticket_supply: u32,
+    /// Number of the tier's tickets awarded or sold so far
+    pub tickets_awarded: u32,
+    /// Starting price for the tier's Dutch auction (in lamports)
+    pub start_price: u64,
+    /// Ending price for the tier's Dutch auction, or its reserve price in a sealed-bid auction (in lamports)
+    pub end_price: u64,
+    /// How the price falls from `start_price` to `end_price`
+    pub price_curve: PriceCurve,
+    /// Lowest bid amount awarded one of the tier's tickets so far (0 if none)
+    pub lowest_awarded_bid: u64,
+    /// The price at which the tier's auction closed (0 until the event is finalized)
+    pub close_price: u64,
+    /// Number of the tier's tickets awarded to presale bids, which pay their presale price rather than the close price
+    pub presale_tickets_awarded: u32,
+    /// Total paid for the tier's presale tickets
+    pub presale_proceeds: u64,
+}
+
+impl TicketTier {
+    pub const INIT_SPACE: usize = 4 + MAX_METADATA_URL_LEN + // metadata_url string
+                               4 + // ticket_supply
+                               4 + // tickets_awarded
+                               8 + // start_price
+                               8 + // end_price
+                               PriceCurve::INIT_SPACE + // price_curve
+                               8 + // lowest_awarded_bid
+                               8 + // close_price
+                               4 + // presale_tickets_awarded
+                               8; // presale_proceeds
+
+    /// Check if every ticket in the tier has been awarded or sold
+    pub fn is_sold_out(&self) -> bool {
+        self.tickets_awarded >= self.ticket_supply
+    }
+
+    /// Record the per-ticket price of a public bid that was awarded one of the tier's tickets, tracking the lowest one
+    pub fn record_awarded_bid(&mut self, unit_price: u64) {
+        if self.lowest_awarded_bid == 0 || unit_price < self.lowest_awarded_bid {
+            self.lowest_awarded_bid = unit_price;
+        }
+    }
+
+    /// Record a ticket awarded to a presale bid at its fixed `unit_price`, kept out of the public clearing price
+    pub fn record_presale_award(&mut self, unit_price: u64) -> Option<()> {
+        self.presale_tickets_awarded = self.presale_tickets_awarded.checked_add(1)?;
+        self.presale_proceeds = self.presale_proceeds.checked_add(unit_price)?;
+        Some(())
+    }
+
+    /// The uniform price every public winner in the tier pays: the lowest awarded public bid, or the end price if there was none
+    pub fn clearing_price(&self) -> u64 {
+        if self.tickets_awarded > 0 && self.lowest_awarded_bid > 0 {
+            self.lowest_awarded_bid
+        } else {
+            self.end_price
+        }
+    }
+
+    /// Fixed presale price: `start_price` less `discount_bps`, but never below the `end_price` reserve
+    pub fn presale_price(&self, discount_bps: u16) -> u64 {
+        let discount = (self.start_price as u128) * (discount_bps as u128) / BPS_DENOMINATOR;
+        self.start_price.saturating_sub(discount as u64).max(self.end_price)
+    }
+
+    /// Proceeds owed to the organizer for the tier: every public ticket at the close price, and every presale one at its own
+    pub fn proceeds(&self) -> Option<u64> {
+        let public_tickets = self.tickets_awarded.checked_sub(self.presale_tickets_awarded)? as u64;
+        public_tickets.checked_mul(self.close_price)?.checked_add(self.presale_proceeds)
+    }
+}
+
+impl From<TicketTierArgs> for TicketTier {
+    fn from(args: TicketTierArgs) -> Self {
+        TicketTier {
+            metadata_url: args.metadata_url,
+            ticket_supply: args.ticket_supply,
+            tickets_awarded: 0,
+            start_price: args.start_price,
+            end_price: args.end_price,
+            price_curve: args.price_curve,
+            lowest_awarded_bid: 0,
+            close_price: 0,
+            presale_tickets_awarded: 0,
+            presale_proceeds: 0,
+        }
+    }
+}
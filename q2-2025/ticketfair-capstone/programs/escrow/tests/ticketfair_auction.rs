@@ -1,30 +1,31 @@
-use anchor_lang::prelude::*;
-use anchor_lang::solana_program::system_program;
-use anchor_lang::prelude::Pubkey;
-use anchor_lang::prelude::ToAccountInfo;
-use anchor_lang::prelude::Signer;
-use anchor_lang::prelude::Account;
-use anchor_lang::prelude::System;
-use anchor_lang::prelude::Context;
-use anchor_lang::prelude::Result;
+use anchor_lang::prelude::{AccountInfo, Pubkey, Rent};
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_lang::solana_program::system_instruction::MAX_PERMITTED_DATA_LENGTH;
+use anchor_lang::{system_program, AccountSerialize, Accounts};
+use anchor_spl::token_interface::spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+};
+use anchor_spl::token_interface::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_interface::spl_token_2022::solana_program::program_pack::Pack;
+use std::collections::BTreeSet;
 
 // Import program state
 use escrow::state;
 use escrow::constants::*;
+use escrow::error::ErrorCode;
+use escrow::handlers::{
+    derive_event_pda, merkle_tree_account_size, merkle_tree_dimensions,
+    is_supported_payment_mint, parse_ed25519_instruction, AcceptProtocolAdminAccountConstraints,
+    AcceptProtocolAdminAccountConstraintsBumps, CheckInTicketAccountConstraints,
+    CheckInTicketAccountConstraintsBumps, RefundBidAccountConstraints,
+    RefundBidAccountConstraintsBumps, SetProtocolFeeAccountConstraints,
+    SetProtocolFeeAccountConstraintsBumps, ATTESTATION_MESSAGE_LEN,
+};
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anchor_lang::prelude::*;
-    use anchor_lang::solana_program::clock::Clock;
-    use anchor_lang::solana_program::sysvar;
-    use anchor_lang::ToAccountInfos;
-    use anchor_lang::prelude::Signer;
-    use anchor_lang::prelude::Account;
-    use anchor_lang::prelude::System;
-    use anchor_lang::prelude::Context;
-    use anchor_lang::prelude::Result;
-    use std::str::FromStr;
 
     // Helper: Generate a test pubkey
     fn test_pubkey(seed: u8) -> Pubkey {
@@ -36,6 +37,126 @@ mod tests {
         1_700_000_000 // Fixed timestamp for deterministic tests
     }
 
+    // Helper: An account as the runtime would pass it in, leaked so it lives as long as the test
+    fn test_account(key: Pubkey, owner: Pubkey, data: Vec<u8>, is_signer: bool, executable: bool) -> AccountInfo<'static> {
+        AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(1_000_000_000)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            executable,
+            0,
+        )
+    }
+
+    // Helper: A program-owned account holding `value`
+    fn program_account<T: AccountSerialize>(key: Pubkey, value: &T) -> AccountInfo<'static> {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        test_account(key, escrow::ID, data, false, false)
+    }
+
+    // Helper: A wallet
+    fn wallet_account(key: Pubkey, is_signer: bool) -> AccountInfo<'static> {
+        test_account(key, system_program::ID, vec![], is_signer, false)
+    }
+
+    // Helper: A finalized event at its PDA
+    fn test_event_at_pda(organizer: Pubkey, event_id: u32) -> (Pubkey, state::Event) {
+        let (event_key, bump) = derive_event_pda(&organizer, event_id, &escrow::ID);
+        let event = state::Event {
+            organizer,
+            event_id,
+            metadata_url: "https://example.com/event.json".to_string(),
+            ticket_supply: 10,
+            tickets_awarded: 1,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 10,
+                tickets_awarded: 1,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 400_000,
+                close_price: 400_000,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
+            auction_start_time: test_time(),
+            auction_end_time: test_time() + 3600,
+            event_start_time: test_time() + 86_400,
+            gate_authority: organizer,
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 900_000,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Finalized,
+            bump,
+            payment_mint: None,
+            merkle_tree: test_pubkey(2),
+            tickets_minted: 10,
+        };
+        (event_key, event)
+    }
+
+    // Helper: A pending bid for tier 0 at its PDA
+    fn test_bid_at_pda(event: Pubkey, bidder: Pubkey) -> (Pubkey, state::Bid) {
+        let (bid_key, bump) = Pubkey::find_program_address(&[b"bid", event.as_ref(), bidder.as_ref(), &[0]], &escrow::ID);
+        let bid = state::Bid {
+            bidder,
+            event,
+            tier: 0,
+            amount: 900_000,
+            quantity: 1,
+            tickets_awarded: 0,
+            refunded_amount: 0,
+            settled: false,
+            commitment: [0; 32],
+            revealed_price: 0,
+            presale: false,
+            status: state::BidStatus::Pending,
+            bump,
+        };
+        (bid_key, bid)
+    }
+
+    // Helper: Run refund_bid's account validation
+    fn refund_bid_accounts(bidder: Pubkey, event: (Pubkey, &state::Event), bid: (Pubkey, &state::Bid)) -> anchor_lang::Result<()> {
+        let (event_pda, _) = Pubkey::find_program_address(&[b"escrow", event.0.as_ref()], &escrow::ID);
+        let mut accounts: &[AccountInfo<'static>] = Box::leak(Box::new([
+            wallet_account(bidder, true),
+            program_account(event.0, event.1),
+            program_account(bid.0, bid.1),
+            wallet_account(event_pda, false),
+            // Optional token accounts left out
+            wallet_account(escrow::ID, false),
+            wallet_account(escrow::ID, false),
+            wallet_account(escrow::ID, false),
+            wallet_account(escrow::ID, false),
+            test_account(system_program::ID, Pubkey::default(), vec![], false, true),
+        ]));
+        RefundBidAccountConstraints::try_accounts(
+            &escrow::ID,
+            &mut accounts,
+            &[0], // tier
+            &mut RefundBidAccountConstraintsBumps::default(),
+            &mut BTreeSet::new(),
+        )
+        .map(|_| ())
+    }
+
     #[test]
     fn test_event_creation() {
         // Simulate event creation with valid parameters
@@ -49,20 +170,47 @@ mod tests {
         let auction_end_time = auction_start_time + 3600;
 
         // Simulate event account
-        let mut event = state::Event {
+        let event = state::Event {
             organizer,
+            event_id: 0,
             metadata_url: metadata_url.clone(),
             ticket_supply,
             tickets_awarded: 0,
-            start_price,
-            end_price,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: metadata_url.clone(),
+                ticket_supply,
+                tickets_awarded: 0,
+                start_price,
+                end_price,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 0,
+                close_price: 0,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
             auction_start_time,
             auction_end_time,
-            auction_close_price: 0,
-            status: EVENT_STATUS_CREATED,
+            event_start_time: auction_end_time + 86_400,
+            gate_authority: organizer,
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 0,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Created,
             bump: 255,
+            payment_mint: None,
             merkle_tree,
-            cnft_asset_ids: vec![],
+            tickets_minted: 0,
         };
 
         // Assert event fields
@@ -70,14 +218,15 @@ mod tests {
         assert_eq!(event.metadata_url, metadata_url);
         assert_eq!(event.ticket_supply, ticket_supply);
         assert_eq!(event.tickets_awarded, 0);
-        assert_eq!(event.start_price, start_price);
-        assert_eq!(event.end_price, end_price);
+        assert_eq!(event.tiers[0].start_price, start_price);
+        assert_eq!(event.tiers[0].end_price, end_price);
         assert_eq!(event.auction_start_time, auction_start_time);
         assert_eq!(event.auction_end_time, auction_end_time);
-        assert_eq!(event.status, EVENT_STATUS_CREATED);
+        assert_eq!(event.status, state::EventStatus::Created);
         assert_eq!(event.merkle_tree, merkle_tree);
-        // Asset IDs should be empty at creation
-        assert!(event.cnft_asset_ids.is_empty());
+        // No ticket is minted at creation
+        assert_eq!(event.tickets_minted, 0);
+        assert!(!event.is_inventory_complete());
     }
 
     #[test]
@@ -85,24 +234,51 @@ mod tests {
         // Simulate event account
         let mut event = state::Event {
             organizer: test_pubkey(1),
+            event_id: 0,
             metadata_url: "https://example.com/event.json".to_string(),
             ticket_supply: 10,
             tickets_awarded: 0,
-            start_price: 1_000_000,
-            end_price: 100_000,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 10,
+                tickets_awarded: 0,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 0,
+                close_price: 0,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
             auction_start_time: test_time(),
             auction_end_time: test_time() + 3600,
-            auction_close_price: 0,
-            status: EVENT_STATUS_CREATED,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 0,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Created,
             bump: 255,
+            payment_mint: None,
             merkle_tree: test_pubkey(2),
-            cnft_asset_ids: vec![],
+            tickets_minted: 0,
         };
 
         // Test activation
-        assert_eq!(event.status, EVENT_STATUS_CREATED);
-        event.status = EVENT_STATUS_ACTIVE;
-        assert_eq!(event.status, EVENT_STATUS_ACTIVE);
+        assert_eq!(event.status, state::EventStatus::Created);
+        event.status = state::EventStatus::Active;
+        assert_eq!(event.status, state::EventStatus::Active);
     }
 
     #[test]
@@ -111,18 +287,26 @@ mod tests {
         let bidder = test_pubkey(3);
         let event = test_pubkey(4);
         let amount = 1_000_000u64;
-        let mut bid = state::Bid {
+        let bid = state::Bid {
             bidder,
             event,
+            tier: 0,
             amount,
-            status: BID_STATUS_PENDING,
+            quantity: 1,
+            tickets_awarded: 0,
+            refunded_amount: 0,
+            settled: false,
+            commitment: [0; 32],
+            revealed_price: 0,
+            presale: false,
+            status: state::BidStatus::Pending,
             bump: 254,
         };
         // Assert bid fields
         assert_eq!(bid.bidder, bidder);
         assert_eq!(bid.event, event);
         assert_eq!(bid.amount, amount);
-        assert_eq!(bid.status, BID_STATUS_PENDING);
+        assert_eq!(bid.status, state::BidStatus::Pending);
         
         // Test helper method
         assert!(bid.can_award());
@@ -135,18 +319,22 @@ mod tests {
         let owner = test_pubkey(5);
         let event = test_pubkey(6);
         let cnft_asset_id = test_pubkey(7);
-        let mut ticket = state::Ticket {
+        let ticket = state::Ticket {
             owner,
             event,
-            status: TICKET_STATUS_OWNED,
+            status: state::TicketStatus::Owned,
             offchain_ref: String::new(),
             bump: 253,
             cnft_asset_id,
+            index: 0,
+            tier: 0,
+            rent_payer: owner,
+            purchase_price: 0,
         };
         // Assert ticket fields
         assert_eq!(ticket.owner, owner);
         assert_eq!(ticket.event, event);
-        assert_eq!(ticket.status, TICKET_STATUS_OWNED);
+        assert_eq!(ticket.status, state::TicketStatus::Owned);
         assert_eq!(ticket.cnft_asset_id, cnft_asset_id);
         
         // Test helper methods
@@ -159,31 +347,58 @@ mod tests {
         // Simulate event account
         let mut event = state::Event {
             organizer: test_pubkey(1),
+            event_id: 0,
             metadata_url: "https://example.com/event.json".to_string(),
             ticket_supply: 10,
             tickets_awarded: 5, // Some tickets awarded
-            start_price: 1_000_000,
-            end_price: 100_000,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 10,
+                tickets_awarded: 5, // Some tickets awarded
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 500_000, // Lowest of the awarded bids
+                close_price: 0, // Not finalized yet
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
             auction_start_time: test_time() - 7200, // 2 hours ago
             auction_end_time: test_time() - 3600, // 1 hour ago (auction ended)
-            auction_close_price: 0, // Not finalized yet
-            status: EVENT_STATUS_ACTIVE,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 0,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Active,
             bump: 255,
+            payment_mint: None,
             merkle_tree: test_pubkey(2),
-            cnft_asset_ids: vec![],
+            tickets_minted: 10,
         };
         
         // Test finalization condition
         assert!(event.can_finalize(test_time())); // Should be finalizable now
         
-        // Finalize auction
-        let close_price = 500_000; // Between start and end price
-        event.auction_close_price = close_price;
-        event.status = EVENT_STATUS_FINALIZED;
+        // Finalize auction at the uniform clearing price
+        let close_price = 500_000; // Lowest awarded bid
+        event.tiers[0].close_price = event.tiers[0].clearing_price();
+        event.status = state::EventStatus::Finalized;
         
         // Verify the auction is finalized
-        assert_eq!(event.auction_close_price, close_price);
-        assert_eq!(event.status, EVENT_STATUS_FINALIZED);
+        assert_eq!(event.tiers[0].close_price, close_price);
+        assert_eq!(event.status, state::EventStatus::Finalized);
         assert!(!event.can_finalize(test_time())); // Cannot finalize again
     }
 
@@ -197,39 +412,66 @@ mod tests {
         
         let event = state::Event {
             organizer: test_pubkey(1),
+            event_id: 0,
             metadata_url: "https://example.com/event.json".to_string(),
             ticket_supply: 10,
             tickets_awarded: 0,
-            start_price,
-            end_price,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 10,
+                tickets_awarded: 0,
+                start_price,
+                end_price,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 0,
+                close_price: 0,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
             auction_start_time: start_time,
             auction_end_time: end_time,
-            auction_close_price: 0,
-            status: EVENT_STATUS_ACTIVE,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 0,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Active,
             bump: 255,
+            payment_mint: None,
             merkle_tree: test_pubkey(2),
-            cnft_asset_ids: vec![],
+            tickets_minted: 10,
         };
         
         // Test pricing at different times
-        assert_eq!(event.get_current_auction_price(start_time - 1), start_price); // Before auction
-        assert_eq!(event.get_current_auction_price(start_time), start_price); // At auction start
-        assert_eq!(event.get_current_auction_price(end_time), end_price); // At auction end
-        assert_eq!(event.get_current_auction_price(end_time + 1), end_price); // After auction
+        assert_eq!(event.get_current_auction_price(0, start_time - 1).unwrap(), start_price); // Before auction
+        assert_eq!(event.get_current_auction_price(0, start_time).unwrap(), start_price); // At auction start
+        assert_eq!(event.get_current_auction_price(0, end_time).unwrap(), end_price); // At auction end
+        assert_eq!(event.get_current_auction_price(0, end_time + 1).unwrap(), end_price); // After auction
         
         // Test pricing at halfway point
         let halfway_time = start_time + 1800; // 30 minutes in
         let expected_halfway_price = 550_000; // Half between start and end price
-        assert_eq!(event.get_current_auction_price(halfway_time), expected_halfway_price);
+        assert_eq!(event.get_current_auction_price(0, halfway_time).unwrap(), expected_halfway_price);
         
         // Test pricing at quarter points
         let quarter_time = start_time + 900; // 15 minutes in
         let expected_quarter_price = 775_000; // 1/4 between start and end price
-        assert_eq!(event.get_current_auction_price(quarter_time), expected_quarter_price);
+        assert_eq!(event.get_current_auction_price(0, quarter_time).unwrap(), expected_quarter_price);
         
         let three_quarter_time = start_time + 2700; // 45 minutes in
         let expected_three_quarter_price = 325_000; // 3/4 between start and end price
-        assert_eq!(event.get_current_auction_price(three_quarter_time), expected_three_quarter_price);
+        assert_eq!(event.get_current_auction_price(0, three_quarter_time).unwrap(), expected_three_quarter_price);
     }
 
     #[test]
@@ -238,47 +480,86 @@ mod tests {
         let mut bid = state::Bid {
             bidder: test_pubkey(8),
             event: test_pubkey(9),
+            tier: 0,
             amount: 2_000_000,
-            status: BID_STATUS_PENDING, // Pending
+            quantity: 1,
+            tickets_awarded: 0,
+            refunded_amount: 0,
+            settled: false,
+            commitment: [0; 32],
+            revealed_price: 0,
+            presale: false,
+            status: state::BidStatus::Pending,
             bump: 252,
         };
         // Refund logic: losing bid
-        bid.status = BID_STATUS_REFUNDED; // Refunded
-        assert_eq!(bid.status, BID_STATUS_REFUNDED);
+        bid.status = state::BidStatus::Refunded;
+        assert_eq!(bid.status, state::BidStatus::Refunded);
         assert!(!bid.can_refund()); // Can't refund again
         
         // Create event to simulate a finalized auction
-        let mut event = state::Event {
+        let event = state::Event {
             organizer: test_pubkey(10),
+            event_id: 0,
             metadata_url: "https://example.com/event2.json".to_string(),
             ticket_supply: 10,
             tickets_awarded: 5,
-            start_price: 2_000_000,
-            end_price: 1_000_000,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event2.json".to_string(),
+                ticket_supply: 10,
+                tickets_awarded: 5,
+                start_price: 2_000_000,
+                end_price: 1_000_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 0,
+                close_price: 1_500_000, // Auction finalized with this price
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
             auction_start_time: test_time() - 7200,
             auction_end_time: test_time() - 3600,
-            auction_close_price: 1_500_000, // Auction finalized with this price
-            status: EVENT_STATUS_FINALIZED,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 0,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Finalized,
             bump: 251,
+            payment_mint: None,
             merkle_tree: test_pubkey(11),
-            cnft_asset_ids: vec![],
+            tickets_minted: 10,
         };
 
         // Simulate a partial refund for a winning bid (overbid)
-        let mut awarded_bid = state::Bid {
+        let awarded_bid = state::Bid {
             bidder: test_pubkey(12),
             event: event.merkle_tree,
+            tier: 0,
             amount: 2_000_000, // Bid was at this higher amount
-            status: BID_STATUS_AWARDED, // Awarded
+            quantity: 1,
+            tickets_awarded: 1,
+            refunded_amount: 0,
+            settled: false,
+            commitment: [0; 32],
+            revealed_price: 0,
+            presale: false,
+            status: state::BidStatus::Awarded,
             bump: 250,
         };
         
         // Calculate expected refund amount
-        let refund_amount = if awarded_bid.amount > event.auction_close_price {
-            awarded_bid.amount - event.auction_close_price
-        } else {
-            0
-        };
+        let refund_amount = awarded_bid.amount.saturating_sub(event.tiers[0].close_price);
         assert_eq!(refund_amount, 500_000); // Should get a partial refund of 0.5 SOL
     }
 
@@ -287,36 +568,1377 @@ mod tests {
         // Simulate cNFT minting, transfer, and burn logic
         let mut event = state::Event {
             organizer: test_pubkey(12),
+            event_id: 0,
             metadata_url: "https://example.com/event.json".to_string(),
             ticket_supply: 2,
             tickets_awarded: 0,
-            start_price: 1_000_000,
-            end_price: 100_000,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 2,
+                tickets_awarded: 0,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 0,
+                close_price: 0,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
             auction_start_time: test_time(),
             auction_end_time: test_time() + 3600,
-            auction_close_price: 0,
-            status: EVENT_STATUS_CREATED,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 0,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Created,
             bump: 250,
+            payment_mint: None,
             merkle_tree: test_pubkey(13),
-            cnft_asset_ids: vec![],
+            tickets_minted: 0,
+        };
+        let mut inventory = state::TicketInventory {
+            event: test_pubkey(20),
+            page: event.next_inventory_page(),
+            asset_ids: vec![],
+            awarded: vec![],
+            bump: 248,
         };
-        // Mint cNFTs (simulate by pushing asset IDs)
+        // Mint cNFTs (simulate by adding asset IDs to the inventory)
         let asset_id1 = test_pubkey(14);
         let asset_id2 = test_pubkey(15);
-        event.cnft_asset_ids.push(asset_id1);
-        event.cnft_asset_ids.push(asset_id2);
-        assert_eq!(event.cnft_asset_ids.len(), 2);
+        inventory.add_asset(asset_id1).unwrap();
+        inventory.add_asset(asset_id2).unwrap();
+        event.tickets_minted += 2;
+        assert_eq!(inventory.asset_ids.len(), 2);
+        assert!(event.is_inventory_complete());
         // Transfer cNFT (simulate by removing from event and assigning to ticket)
-        let mut ticket = state::Ticket {
+        let _ticket = state::Ticket {
             owner: test_pubkey(16),
             event: event.merkle_tree,
-            status: TICKET_STATUS_OWNED,
+            status: state::TicketStatus::Owned,
             offchain_ref: String::new(),
             bump: 249,
             cnft_asset_id: asset_id1,
+            index: 0,
+            tier: 0,
+            rent_payer: test_pubkey(16),
+            purchase_price: 0,
+        };
+        // Burn unsold cNFT (simulate by removing from the inventory)
+        inventory.asset_ids.retain(|&id| id != asset_id2);
+        assert_eq!(inventory.asset_ids.len(), 1);
+    }
+
+    #[test]
+    fn test_award_asset_lookup() {
+        let asset_id1 = test_pubkey(17);
+        let asset_id2 = test_pubkey(18);
+        let mut inventory = state::TicketInventory {
+            event: test_pubkey(20),
+            page: 0,
+            asset_ids: vec![asset_id1, asset_id2],
+            awarded: vec![false, false],
+            bump: 250,
+        };
+
+        let tickets = 0..2;
+
+        // Assets minted for the event can be awarded
+        assert_eq!(inventory.find_unawarded_asset(&asset_id2, &tickets).unwrap(), 1);
+
+        // An awarded asset cannot be awarded again
+        inventory.awarded[1] = true;
+        assert!(inventory.find_unawarded_asset(&asset_id2, &tickets).is_err());
+        assert_eq!(inventory.find_unawarded_asset(&asset_id1, &tickets).unwrap(), 0);
+
+        // Assets from other events are rejected
+        assert!(inventory.find_unawarded_asset(&test_pubkey(19), &tickets).is_err());
+    }
+
+    #[test]
+    fn test_event_pda_per_organizer_event_id() {
+        // One organizer can run many events, each at its own PDA
+        let organizer = test_pubkey(20);
+        let (first_event, _) = derive_event_pda(&organizer, 0, &escrow::ID);
+        let (second_event, _) = derive_event_pda(&organizer, 1, &escrow::ID);
+        assert_ne!(first_event, second_event);
+
+        // The same event ID for another organizer is a different event
+        let (other_organizer_event, _) = derive_event_pda(&test_pubkey(21), 0, &escrow::ID);
+        assert_ne!(first_event, other_organizer_event);
+    }
+
+    #[test]
+    fn test_clearing_price() {
+        let mut event = state::Event {
+            organizer: test_pubkey(1),
+            event_id: 0,
+            metadata_url: "https://example.com/event.json".to_string(),
+            ticket_supply: 3,
+            tickets_awarded: 0,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 3,
+                tickets_awarded: 0,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 0,
+                close_price: 0,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
+            auction_start_time: test_time() - 7200,
+            auction_end_time: test_time() - 3600,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 0,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Active,
+            bump: 255,
+            payment_mint: None,
+            merkle_tree: test_pubkey(2),
+            tickets_minted: 3,
+        };
+
+        // With no winners the auction clears at the end price
+        assert_eq!(event.tiers[0].clearing_price(), 100_000);
+
+        // Winners all pay the lowest awarded bid
+        for amount in [900_000, 400_000, 700_000] {
+            event.tiers[0].record_awarded_bid(amount);
+            event.record_awarded_ticket(0).unwrap();
+        }
+        assert_eq!(event.tickets_awarded, 3);
+        assert_eq!(event.tiers[0].lowest_awarded_bid, 400_000);
+        assert_eq!(event.tiers[0].clearing_price(), 400_000);
+
+        // No more tickets than the tier holds
+        assert!(event.record_awarded_ticket(0).is_err());
+    }
+
+    #[test]
+    fn test_event_cancellation() {
+        let mut event = state::Event {
+            organizer: test_pubkey(1),
+            event_id: 0,
+            metadata_url: "https://example.com/event.json".to_string(),
+            ticket_supply: 10,
+            tickets_awarded: 0,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 10,
+                tickets_awarded: 0,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 0,
+                close_price: 0,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
+            auction_start_time: test_time(),
+            auction_end_time: test_time() + 3600,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 0,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Created,
+            bump: 255,
+            payment_mint: None,
+            merkle_tree: test_pubkey(2),
+            tickets_minted: 0,
+        };
+
+        // Created and Active events can be cancelled at any time
+        assert!(event.can_cancel(test_time()));
+        event.status = state::EventStatus::Active;
+        assert!(event.can_cancel(test_time() + 100_000));
+
+        // Finalized events can only be cancelled until check-in opens
+        event.status = state::EventStatus::Finalized;
+        assert!(event.can_cancel(test_time() + 7200));
+        assert!(!event.can_cancel(event.check_in_opens_at()));
+        assert!(!event.can_cancel(test_time() + 86_400));
+
+        // Cancelled events cannot be cancelled again
+        event.status = state::EventStatus::Cancelled;
+        assert!(!event.can_cancel(test_time()));
+    }
+
+    #[test]
+    fn test_proceeds_withdrawal() {
+        // Three bids escrowed, two of them won, auction cleared at 400_000
+        let mut event = state::Event {
+            organizer: test_pubkey(1),
+            event_id: 0,
+            metadata_url: "https://example.com/event.json".to_string(),
+            ticket_supply: 2,
+            tickets_awarded: 2,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 2,
+                tickets_awarded: 2,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 400_000,
+                close_price: 400_000,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
+            auction_start_time: test_time() - 7200,
+            auction_end_time: test_time() - 3600,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 900_000 + 400_000 + 300_000,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Finalized,
+            bump: 255,
+            payment_mint: None,
+            merkle_tree: test_pubkey(2),
+            tickets_minted: 2,
+        };
+
+        // The organizer is owed both tickets at the clearing price
+        assert_eq!(event.total_proceeds(), Some(800_000));
+        // The escrow must keep the winner's overpayment and the loser's full bid
+        assert_eq!(event.outstanding_refunds(), Some(500_000 + 300_000));
+
+        // Refunds paid out reduce what the escrow must keep
+        event.total_refunded = 500_000;
+        assert_eq!(event.outstanding_refunds(), Some(300_000));
+
+        // Withdrawing with a small refund still unclaimed leaves the SOL escrow rent-exempt, thanks to its reserve
+        let rent = Rent::default();
+        let rent_reserve = rent.minimum_balance(0);
+        event.total_refunded = 799_000;
+        let escrow_balance = rent_reserve + 800_000 + 1_000;
+        assert!(event.escrow_covers_withdrawal(800_000, escrow_balance, rent_reserve));
+        assert!(rent.is_exempt(escrow_balance - 800_000, 0));
+        assert!(!event.escrow_covers_withdrawal(800_000, escrow_balance - 1, rent_reserve));
+        event.total_refunded = 500_000;
+
+        // After withdrawing, the event can no longer be cancelled
+        assert!(event.can_cancel(test_time()));
+        event.proceeds_withdrawn = 800_000;
+        assert!(!event.can_cancel(test_time()));
+    }
+
+    #[test]
+    fn test_check_in_window() {
+        let event_start_time = test_time() + 86_400;
+        let mut event = state::Event {
+            organizer: test_pubkey(1),
+            event_id: 0,
+            metadata_url: "https://example.com/event.json".to_string(),
+            ticket_supply: 10,
+            tickets_awarded: 1,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 10,
+                tickets_awarded: 1,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 400_000,
+                close_price: 400_000,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
+            auction_start_time: test_time(),
+            auction_end_time: test_time() + 3600,
+            event_start_time,
+            gate_authority: test_pubkey(3),
+            check_in_opens_before: 3600,
+            check_in_closes_after: 7200,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 400_000,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Finalized,
+            bump: 255,
+            payment_mint: None,
+            merkle_tree: test_pubkey(2),
+            tickets_minted: 10,
+        };
+
+        // Open from an hour before the event starts until two hours after
+        assert!(!event.is_check_in_open(event_start_time - 3601));
+        assert!(event.is_check_in_open(event_start_time - 3600));
+        assert!(event.is_check_in_open(event_start_time + 7200));
+        assert!(!event.is_check_in_open(event_start_time + 7201));
+
+        // Cancellation closes as check-in opens, so no checked-in ticket is ever part of a cancelled event
+        assert!(event.can_cancel(event_start_time - 3601));
+        assert!(!event.can_cancel(event_start_time - 3600));
+
+        // Cancelled events never open check-in
+        event.status = state::EventStatus::Cancelled;
+        assert!(!event.is_check_in_open(event_start_time));
+
+        // A checked-in ticket cannot be claimed again
+        let mut ticket = state::Ticket {
+            owner: test_pubkey(5),
+            event: test_pubkey(6),
+            status: state::TicketStatus::Owned,
+            offchain_ref: String::new(),
+            bump: 253,
+            cnft_asset_id: test_pubkey(7),
+            index: 0,
+            tier: 0,
+            rent_payer: test_pubkey(5),
+            purchase_price: 0,
+        };
+        assert!(ticket.can_claim());
+        ticket.status = state::TicketStatus::Claimed;
+        assert!(!ticket.can_claim());
+        assert!(!ticket.can_refund());
+    }
+
+    #[test]
+    fn test_resale_terms() {
+        let mut event = state::Event {
+            organizer: test_pubkey(1),
+            event_id: 0,
+            metadata_url: "https://example.com/event.json".to_string(),
+            ticket_supply: 10,
+            tickets_awarded: 1,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 10,
+                tickets_awarded: 1,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 400_000,
+                close_price: 400_000,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
+            auction_start_time: test_time() - 7200,
+            auction_end_time: test_time() - 3600,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 400_000,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Active,
+            bump: 255,
+            payment_mint: None,
+            merkle_tree: test_pubkey(2),
+            tickets_minted: 10,
+        };
+
+        // Resale opens once a finalized event can no longer be cancelled, and closes when the event starts
+        assert!(!event.is_resale_open(test_time()));
+        event.status = state::EventStatus::Finalized;
+        assert!(!event.is_resale_open(test_time()));
+        assert!(event.is_resale_open(event.check_in_opens_at()));
+        event.proceeds_withdrawn = 400_000;
+        assert!(event.is_resale_open(test_time()));
+        assert!(!event.is_resale_open(test_time() + 86_400));
+
+        // Default cap is 110% of the close price, with a 5% royalty
+        assert_eq!(event.max_resale_price(0).ok(), Some(440_000));
+        assert_eq!(event.resale_royalty(440_000), Some(22_000));
+
+        // Listed tickets cannot be listed again, checked in or refunded
+        let mut ticket = state::Ticket {
+            owner: test_pubkey(5),
+            event: test_pubkey(6),
+            status: state::TicketStatus::Owned,
+            offchain_ref: String::new(),
+            bump: 253,
+            cnft_asset_id: test_pubkey(7),
+            index: 0,
+            tier: 0,
+            rent_payer: test_pubkey(5),
+            purchase_price: 0,
+        };
+        assert!(ticket.can_list());
+        ticket.status = state::TicketStatus::Listed;
+        assert!(!ticket.can_list());
+        assert!(!ticket.can_claim());
+        assert!(!ticket.can_refund());
+    }
+
+    #[test]
+    fn test_fixed_price_sale() {
+        // One of two tickets won at auction, cleared at 400_000
+        let mut event = state::Event {
+            organizer: test_pubkey(1),
+            event_id: 0,
+            metadata_url: "https://example.com/event.json".to_string(),
+            ticket_supply: 2,
+            tickets_awarded: 1,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 2,
+                tickets_awarded: 1,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 400_000,
+                close_price: 400_000,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
+            auction_start_time: test_time() - 7200,
+            auction_end_time: test_time() - 3600,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 600_000,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Finalized,
+            bump: 255,
+            payment_mint: None,
+            merkle_tree: test_pubkey(2),
+            tickets_minted: 2,
+        };
+        let mut inventory = state::TicketInventory {
+            event: test_pubkey(20),
+            page: 0,
+            asset_ids: vec![test_pubkey(10), test_pubkey(11)],
+            awarded: vec![true, false],
+            bump: 250,
+        };
+        assert_eq!(event.outstanding_refunds(), Some(200_000));
+
+        // The leftover ticket sells at the close price, all of it owed to the organizer
+        let index = inventory.find_unawarded_asset(&test_pubkey(11), &event.tier_tickets(0).unwrap()).unwrap();
+        inventory.awarded[index] = true;
+        event.record_awarded_ticket(0).unwrap();
+        event.total_escrowed += event.tiers[0].close_price;
+
+        assert_eq!(event.total_proceeds(), Some(800_000));
+        assert_eq!(event.outstanding_refunds(), Some(200_000));
+        assert!(inventory.find_unawarded_asset(&test_pubkey(11), &event.tier_tickets(0).unwrap()).is_err());
+
+        // Cancelled after the sale, the buyer gets the purchase price back from escrow on their ticket
+        let mut ticket = state::Ticket {
+            owner: test_pubkey(5),
+            event: test_pubkey(20),
+            status: state::TicketStatus::Owned,
+            offchain_ref: String::new(),
+            bump: 253,
+            cnft_asset_id: test_pubkey(11),
+            index: 0,
+            tier: 0,
+            rent_payer: test_pubkey(5),
+            purchase_price: event.tiers[0].close_price,
+        };
+        assert!(event.can_cancel(test_time()));
+        event.status = state::EventStatus::Cancelled;
+        assert!(ticket.can_refund_purchase());
+        ticket.status = state::TicketStatus::Refunded;
+        assert!(!ticket.can_refund_purchase());
+
+        // Awarded tickets are refunded through their bid, not on their own
+        ticket.status = state::TicketStatus::Owned;
+        ticket.purchase_price = 0;
+        assert!(!ticket.can_refund_purchase());
+    }
+
+    #[test]
+    fn test_bid_quantity() {
+        // Three tickets bid for at 500_000 each, two of them won
+        let mut bid = state::Bid {
+            bidder: test_pubkey(8),
+            event: test_pubkey(9),
+            tier: 0,
+            amount: 1_500_000,
+            quantity: 3,
+            tickets_awarded: 2,
+            refunded_amount: 0,
+            settled: false,
+            commitment: [0; 32],
+            revealed_price: 0,
+            presale: false,
+            status: state::BidStatus::Awarded,
+            bump: 252,
+        };
+        assert_eq!(bid.unit_price(), Some(500_000));
+        assert!(bid.can_award());
+
+        // Once every ticket is awarded, the bid cannot win more
+        bid.tickets_awarded = 3;
+        assert!(!bid.can_award());
+
+        // Two winning tickets at the 400_000 close price leave 700_000 to refund
+        bid.tickets_awarded = 2;
+        assert_eq!(bid.refund_due(400_000), Some(700_000));
+
+        // The per-wallet limit bounds bid quantities
+        let event = state::Event {
+            organizer: test_pubkey(1),
+            event_id: 0,
+            metadata_url: "https://example.com/event.json".to_string(),
+            ticket_supply: 10,
+            tickets_awarded: 0,
+            max_tickets_per_wallet: 4,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 10,
+                tickets_awarded: 0,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 0,
+                close_price: 0,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: 0,
+            allowlist_root: [0; 32],
+            presale_discount_bps: 0,
+            attestation_issuer: None,
+            auction_start_time: test_time(),
+            auction_end_time: test_time() + 3600,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 0,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Active,
+            bump: 255,
+            payment_mint: None,
+            merkle_tree: test_pubkey(2),
+            tickets_minted: 10,
+        };
+        assert!(!event.is_within_wallet_limit(0));
+        assert!(event.is_within_wallet_limit(4));
+        assert!(!event.is_within_wallet_limit(5));
+    }
+
+    #[test]
+    fn test_allowlist_presale() {
+        let fan = test_pubkey(20);
+        let other_fan = test_pubkey(21);
+        let outsider = test_pubkey(22);
+
+        // Two-leaf allowlist: the root hashes both leaves in sorted order
+        let fan_leaf = hashv(&[fan.as_ref()]).to_bytes();
+        let other_leaf = hashv(&[other_fan.as_ref()]).to_bytes();
+        let (left, right) = if fan_leaf <= other_leaf { (fan_leaf, other_leaf) } else { (other_leaf, fan_leaf) };
+        let allowlist_root = hashv(&[&left, &right]).to_bytes();
+
+        let mut event = state::Event {
+            organizer: test_pubkey(1),
+            event_id: 0,
+            metadata_url: "https://example.com/event.json".to_string(),
+            ticket_supply: 10,
+            tickets_awarded: 0,
+            max_tickets_per_wallet: 2,
+            tiers: vec![state::TicketTier {
+                metadata_url: "https://example.com/event.json".to_string(),
+                ticket_supply: 10,
+                tickets_awarded: 0,
+                start_price: 1_000_000,
+                end_price: 100_000,
+                price_curve: state::PriceCurve::Linear,
+                lowest_awarded_bid: 0,
+                close_price: 0,
+                presale_tickets_awarded: 0,
+                presale_proceeds: 0,
+            }],
+            auction_type: state::AuctionType::Dutch,
+            presale_start_time: test_time() - 3600,
+            allowlist_root,
+            presale_discount_bps: 2_000,
+            attestation_issuer: None,
+            auction_start_time: test_time(),
+            auction_end_time: test_time() + 3600,
+            event_start_time: test_time() + 86_400,
+            gate_authority: test_pubkey(1),
+            check_in_opens_before: DEFAULT_CHECK_IN_OPENS_BEFORE,
+            check_in_closes_after: DEFAULT_CHECK_IN_CLOSES_AFTER,
+            resale_cap_bps: DEFAULT_RESALE_CAP_BPS,
+            resale_royalty_bps: DEFAULT_RESALE_ROYALTY_BPS,
+            protocol_fee_bps: 0,
+            total_escrowed: 0,
+            total_refunded: 0,
+            proceeds_withdrawn: 0,
+            status: state::EventStatus::Active,
+            bump: 255,
+            payment_mint: None,
+            merkle_tree: test_pubkey(2),
+            tickets_minted: 10,
+        };
+
+        // Open from the presale start until the auction starts
+        assert!(!event.is_presale_open(test_time() - 3601));
+        assert!(event.is_presale_open(test_time() - 1));
+        assert!(!event.is_presale_open(test_time()));
+
+        // Allowlisted wallets prove membership with the sibling leaf
+        assert!(event.is_on_allowlist(&fan, &[other_leaf]));
+        assert!(event.is_on_allowlist(&other_fan, &[fan_leaf]));
+        assert!(!event.is_on_allowlist(&outsider, &[fan_leaf]));
+        assert!(!event.is_on_allowlist(&fan, &[]));
+
+        // 20% off the start price
+        assert_eq!(event.presale_price(0).unwrap(), 800_000);
+
+        // Discounts stop at the end price reserve, right up to a 100% discount
+        event.presale_discount_bps = 8_999;
+        assert_eq!(event.presale_price(0).unwrap(), 100_100);
+        event.presale_discount_bps = 9_000;
+        assert_eq!(event.presale_price(0).unwrap(), 100_000);
+        event.presale_discount_bps = 9_001;
+        assert_eq!(event.presale_price(0).unwrap(), 100_000);
+        event.presale_discount_bps = 10_000;
+        assert_eq!(event.presale_price(0).unwrap(), 100_000);
+
+        // A presale winner pays its own fixed price and leaves the public clearing price alone
+        event.presale_discount_bps = 2_000;
+        let presale_price = event.presale_price(0).unwrap();
+        event.tiers[0].record_presale_award(presale_price).unwrap();
+        event.record_awarded_ticket(0).unwrap();
+        event.tiers[0].record_awarded_bid(600_000);
+        event.record_awarded_ticket(0).unwrap();
+        assert_eq!(event.tiers[0].clearing_price(), 600_000);
+        event.tiers[0].close_price = event.tiers[0].clearing_price();
+        assert_eq!(event.total_proceeds(), Some(800_000 + 600_000));
+
+        // The presale bid is settled at its own price, a public one at the close price
+        let mut bid = state::Bid {
+            bidder: fan,
+            event: test_pubkey(9),
+            tier: 0,
+            amount: 2 * presale_price,
+            quantity: 2,
+            tickets_awarded: 1,
+            refunded_amount: 0,
+            settled: false,
+            commitment: [0; 32],
+            revealed_price: 0,
+            presale: true,
+            status: state::BidStatus::Awarded,
+            bump: 252,
+        };
+        assert_eq!(bid.refund_due(600_000), Some(presale_price));
+        bid.presale = false;
+        bid.amount = 2 * 700_000;
+        assert_eq!(bid.refund_due(600_000), Some(2 * 700_000 - 600_000));
+
+        // No presale without a start time
+        event.presale_start_time = 0;
+        assert!(!event.is_presale_open(test_time() - 1));
+    }
+
+    /// Build an ed25519 program instruction the way the native program lays it out, signature left zeroed
+    fn ed25519_instruction_data(signer: &Pubkey, message: &[u8], data_instruction_index: u16) -> Vec<u8> {
+        let public_key_offset: u16 = 16;
+        let signature_offset: u16 = public_key_offset + 32;
+        let message_offset: u16 = signature_offset + 64;
+
+        let mut data = vec![1u8, 0u8];
+        for value in [
+            signature_offset,
+            data_instruction_index,
+            public_key_offset,
+            data_instruction_index,
+            message_offset,
+            message.len() as u16,
+            data_instruction_index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0u8; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn test_bid_attestation_parsing() {
+        let issuer = test_pubkey(30);
+        let event = test_pubkey(31);
+        let bidder = test_pubkey(32);
+        let expiry = test_time() + 60;
+
+        let mut message = Vec::with_capacity(ATTESTATION_MESSAGE_LEN);
+        message.extend_from_slice(event.as_ref());
+        message.extend_from_slice(bidder.as_ref());
+        message.extend_from_slice(&expiry.to_le_bytes());
+
+        // Signer and message stored in the instruction itself are read back
+        let data = ed25519_instruction_data(&issuer, &message, u16::MAX);
+        let (signer, parsed_message) = parse_ed25519_instruction(&data).unwrap();
+        assert_eq!(signer, issuer);
+        assert_eq!(parsed_message, message.as_slice());
+
+        // Data pointing into another instruction is rejected
+        let data = ed25519_instruction_data(&issuer, &message, 0);
+        assert!(parse_ed25519_instruction(&data).is_none());
+
+        // Truncated data is rejected
+        let data = ed25519_instruction_data(&issuer, &message, u16::MAX);
+        assert!(parse_ed25519_instruction(&data[..data.len() - 1]).is_none());
+        assert!(parse_ed25519_instruction(&[]).is_none());
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        use state::{transition, BidStatus, EventStatus, TicketStatus};
+
+        // Events move forward only, and can be cancelled until they are
+        let mut event_status = EventStatus::Created;
+        assert!(transition(&mut event_status, EventStatus::Finalized).is_err());
+        assert!(transition(&mut event_status, EventStatus::Active).is_ok());
+        assert!(transition(&mut event_status, EventStatus::Active).is_err());
+        assert!(transition(&mut event_status, EventStatus::Finalized).is_ok());
+        assert!(transition(&mut event_status, EventStatus::Cancelled).is_ok());
+        assert!(transition(&mut event_status, EventStatus::Cancelled).is_err());
+        assert_eq!(event_status, EventStatus::Cancelled);
+
+        // A failed transition leaves the status unchanged
+        let mut bid_status = BidStatus::Pending;
+        assert!(transition(&mut bid_status, BidStatus::Pending).is_err());
+        assert_eq!(bid_status, BidStatus::Pending);
+        assert!(transition(&mut bid_status, BidStatus::Awarded).is_ok());
+        assert!(transition(&mut bid_status, BidStatus::Awarded).is_ok());
+        assert!(transition(&mut bid_status, BidStatus::Refunded).is_ok());
+        assert!(transition(&mut bid_status, BidStatus::Awarded).is_err());
+
+        // Used tickets stay used
+        let mut ticket_status = TicketStatus::Owned;
+        assert!(transition(&mut ticket_status, TicketStatus::Listed).is_ok());
+        assert!(transition(&mut ticket_status, TicketStatus::Claimed).is_err());
+        assert!(transition(&mut ticket_status, TicketStatus::Owned).is_ok());
+        assert!(transition(&mut ticket_status, TicketStatus::Claimed).is_ok());
+        assert!(transition(&mut ticket_status, TicketStatus::Refunded).is_err());
+        assert!(transition(&mut ticket_status, TicketStatus::Owned).is_err());
+    }
+
+    #[test]
+    fn test_bid_settlement() {
+        // One ticket won at 900_000, auction cleared at 400_000
+        let mut bid = state::Bid {
+            bidder: test_pubkey(8),
+            event: test_pubkey(9),
+            tier: 0,
+            amount: 900_000,
+            quantity: 1,
+            tickets_awarded: 1,
+            refunded_amount: 0,
+            settled: false,
+            commitment: [0; 32],
+            revealed_price: 0,
+            presale: false,
+            status: state::BidStatus::Awarded,
+            bump: 252,
         };
-        // Burn unsold cNFT (simulate by removing from event)
-        event.cnft_asset_ids.retain(|&id| id != asset_id2);
-        assert_eq!(event.cnft_asset_ids.len(), 1);
+        let refund_due = bid.refund_due(400_000).unwrap();
+        assert_eq!(bid.refund_outstanding(refund_due), Some(500_000));
+
+        // Once paid, asking again pays nothing
+        bid.refunded_amount += 500_000;
+        assert_eq!(bid.refund_outstanding(refund_due), Some(0));
+        assert!(bid.can_refund());
+
+        // If the event is cancelled later, only the rest of the escrow is still owed
+        assert_eq!(bid.refund_outstanding(bid.amount), Some(400_000));
+
+        // Settled bids cannot be refunded again
+        bid.settled = true;
+        assert!(!bid.can_refund());
+    }
+
+    #[test]
+    fn test_refund_bid_account_substitution() {
+        let bidder = test_pubkey(8);
+        let (event_key, event) = test_event_at_pda(test_pubkey(1), 0);
+        let (bid_key, bid) = test_bid_at_pda(event_key, bidder);
+        assert!(refund_bid_accounts(bidder, (event_key, &event), (bid_key, &bid)).is_ok());
+
+        // A bid on another event cannot be refunded out of this event's escrow
+        let (other_event_key, _) = test_event_at_pda(test_pubkey(1), 1);
+        let (other_bid_key, other_bid) = test_bid_at_pda(other_event_key, bidder);
+        assert_eq!(
+            refund_bid_accounts(bidder, (event_key, &event), (other_bid_key, &other_bid)).unwrap_err(),
+            ErrorCode::BidNotForEvent.into()
+        );
+
+        // Nor can another wallet's bid be refunded to the signer
+        let thief = test_pubkey(9);
+        assert_eq!(
+            refund_bid_accounts(thief, (event_key, &event), (bid_key, &bid)).unwrap_err(),
+            ErrorCode::BidderMismatch.into()
+        );
+
+        // An event account that is not at its PDA is rejected outright
+        let mut forged_event = event.clone();
+        forged_event.bump = forged_event.bump.wrapping_sub(1);
+        assert!(refund_bid_accounts(bidder, (event_key, &forged_event), (bid_key, &bid)).is_err());
+    }
+
+    #[test]
+    fn test_check_in_ticket_account_substitution() {
+        let organizer = test_pubkey(1);
+        let owner = test_pubkey(8);
+        let (event_key, event) = test_event_at_pda(organizer, 0);
+        let (other_event_key, _) = test_event_at_pda(organizer, 1);
+        let ticket_at_pda = |event: Pubkey| {
+            let (ticket_key, bump) = Pubkey::find_program_address(
+                &[b"ticket", event.as_ref(), owner.as_ref(), &[0], 0u32.to_le_bytes().as_ref()],
+                &escrow::ID,
+            );
+            let ticket = state::Ticket {
+                owner,
+                event,
+                status: state::TicketStatus::Owned,
+                offchain_ref: "seat-1".to_string(),
+                bump,
+                cnft_asset_id: test_pubkey(3),
+                index: 0,
+                tier: 0,
+                rent_payer: owner,
+                purchase_price: 0,
+            };
+            (ticket_key, ticket)
+        };
+        let check_in_accounts = |gate_authority: Pubkey, ticket: (Pubkey, &state::Ticket)| {
+            let mut accounts: &[AccountInfo<'static>] = Box::leak(Box::new([
+                wallet_account(gate_authority, true),
+                program_account(event_key, &event),
+                program_account(ticket.0, ticket.1),
+            ]));
+            CheckInTicketAccountConstraints::try_accounts(
+                &escrow::ID,
+                &mut accounts,
+                &[],
+                &mut CheckInTicketAccountConstraintsBumps::default(),
+                &mut BTreeSet::new(),
+            )
+            .map(|_| ())
+        };
+
+        let (ticket_key, ticket) = ticket_at_pda(event_key);
+        assert!(check_in_accounts(organizer, (ticket_key, &ticket)).is_ok());
+
+        // A ticket for another event cannot get in
+        let (other_ticket_key, other_ticket) = ticket_at_pda(other_event_key);
+        assert_eq!(
+            check_in_accounts(organizer, (other_ticket_key, &other_ticket)).unwrap_err(),
+            ErrorCode::TicketNotForEvent.into()
+        );
+
+        // Only the event's gate authority can check tickets in
+        assert_eq!(
+            check_in_accounts(owner, (ticket_key, &ticket)).unwrap_err(),
+            ErrorCode::InvalidGateAuthority.into()
+        );
+    }
+
+    #[test]
+    fn test_merkle_tree_sizing() {
+        // The smallest supported tree with a leaf for every ticket
+        assert_eq!(merkle_tree_dimensions(1), Some((3, 8)));
+        assert_eq!(merkle_tree_dimensions(8), Some((3, 8)));
+        assert_eq!(merkle_tree_dimensions(MAX_TICKETS_TEST_MODE), Some((5, 8)));
+        assert_eq!(merkle_tree_dimensions(MAX_TICKETS_PER_EVENT), Some((10, 32)));
+        assert_eq!(merkle_tree_dimensions((1 << 20) + 1), None);
+
+        // Matches the account compression program's size for a depth 14, buffer 64 tree
+        assert_eq!(merkle_tree_account_size(14, 64), 31_800);
+
+        // The largest event's tree is too big to create inside a CPI, but the organizer can allocate it
+        let (max_depth, max_buffer_size) = merkle_tree_dimensions(MAX_TICKETS_PER_EVENT).unwrap();
+        let max_tree_size = merkle_tree_account_size(max_depth, max_buffer_size);
+        assert!(max_tree_size > MAX_PERMITTED_DATA_INCREASE);
+        assert!(max_tree_size as u64 <= MAX_PERMITTED_DATA_LENGTH);
+    }
+
+    #[test]
+    fn test_ticket_inventory_paging() {
+        // Both accounts fit in a single 10 KB account creation
+        const { assert!(8 + state::Event::INIT_SPACE <= 10_240) };
+        const { assert!(8 + state::TicketInventory::INIT_SPACE <= 10_240) };
+
+        // A full-size event mints onto consecutive pages
+        let (_, mut event) = test_event_at_pda(test_pubkey(1), 0);
+        event.ticket_supply = MAX_TICKETS_PER_EVENT;
+        event.tickets_minted = 0;
+        event.status = state::EventStatus::Created;
+        assert_eq!(event.next_inventory_page(), 0);
+        event.tickets_minted = TICKETS_PER_INVENTORY_PAGE - 1;
+        assert_eq!(event.next_inventory_page(), 0);
+        event.tickets_minted = TICKETS_PER_INVENTORY_PAGE;
+        assert_eq!(event.next_inventory_page(), 1);
+        event.tickets_minted = MAX_TICKETS_PER_EVENT - 1;
+        assert_eq!(event.next_inventory_page(), (MAX_TICKETS_PER_EVENT - 1) / TICKETS_PER_INVENTORY_PAGE);
+        assert!(!event.is_inventory_complete());
+        event.tickets_minted = MAX_TICKETS_PER_EVENT;
+        assert!(event.is_inventory_complete());
+
+        // A page takes no more than its capacity
+        let mut inventory = state::TicketInventory {
+            event: test_pubkey(20),
+            page: 0,
+            asset_ids: vec![],
+            awarded: vec![],
+            bump: 250,
+        };
+        for seed in 0..TICKETS_PER_INVENTORY_PAGE {
+            inventory.add_asset(Pubkey::new_from_array([(seed % 256) as u8; 32])).unwrap();
+        }
+        assert_eq!(inventory.remaining_capacity(), 0);
+        assert_eq!(inventory.awarded.len(), TICKETS_PER_INVENTORY_PAGE as usize);
+        assert!(inventory.add_asset(test_pubkey(21)).is_err());
+    }
+
+    #[test]
+    fn test_close_rules() {
+        let (_, mut event) = test_event_at_pda(test_pubkey(1), 0);
+        let check_in_closes = event.event_start_time + event.check_in_closes_after;
+
+        // A finalized event is over once check-in closes
+        assert!(!event.has_ended(event.event_start_time));
+        assert!(!event.has_ended(check_in_closes));
+        assert!(event.has_ended(check_in_closes + 1));
+
+        let mut ticket = state::Ticket {
+            owner: test_pubkey(8),
+            event: test_pubkey(20),
+            status: state::TicketStatus::Owned,
+            offchain_ref: String::new(),
+            bump: 250,
+            cnft_asset_id: test_pubkey(7),
+            index: 0,
+            tier: 0,
+            rent_payer: test_pubkey(1),
+            purchase_price: 0,
+        };
+
+        // An unused ticket can only be closed once the event is over
+        assert!(!ticket.can_close(event.has_ended(event.event_start_time), false));
+        assert!(ticket.can_close(event.has_ended(check_in_closes + 1), false));
+
+        // Used tickets can be closed once the event can no longer be cancelled, refunded ones any time, listed ones never
+        ticket.status = state::TicketStatus::Claimed;
+        assert!(!ticket.can_close(false, event.can_cancel(test_time())));
+        assert!(ticket.can_close(false, event.can_cancel(event.event_start_time)));
+        ticket.status = state::TicketStatus::Refunded;
+        assert!(ticket.can_close(false, true));
+        ticket.status = state::TicketStatus::Listed;
+        assert!(!ticket.can_close(true, false));
+
+        // Unused tickets of a cancelled event stay, as the bid refund needs them
+        event.status = state::EventStatus::Cancelled;
+        assert!(!event.has_ended(check_in_closes + 1));
+    }
+
+    #[test]
+    fn test_sealed_bid_auction() {
+        let (_, mut event) = test_event_at_pda(test_pubkey(1), 0);
+        event.status = state::EventStatus::Active;
+        let reveal_end_time = event.auction_end_time + 3600;
+
+        // Dutch events close when bidding ends, sealed-bid ones when reveals do
+        assert!(!event.is_sealed_bid());
+        assert_eq!(event.auction_close_time(), event.auction_end_time);
+        assert!(!event.is_reveal_open(event.auction_end_time + 1));
+        event.auction_type = state::AuctionType::SealedBid { reveal_end_time };
+        assert!(event.is_sealed_bid());
+        assert_eq!(event.auction_close_time(), reveal_end_time);
+
+        // Reveals open once bidding ends, until the reveal deadline
+        assert!(!event.is_reveal_open(event.auction_end_time));
+        assert!(event.is_reveal_open(event.auction_end_time + 1));
+        assert!(event.is_reveal_open(reveal_end_time));
+        assert!(!event.is_reveal_open(reveal_end_time + 1));
+
+        // The commitment binds price, salt and bidder
+        let bidder = test_pubkey(8);
+        let salt = [42; 32];
+        let commitment = state::sealed_bid_commitment(600_000, &salt, &bidder);
+        assert_eq!(commitment, state::sealed_bid_commitment(600_000, &salt, &bidder));
+        assert_ne!(commitment, state::sealed_bid_commitment(600_001, &salt, &bidder));
+        assert_ne!(commitment, state::sealed_bid_commitment(600_000, &[43; 32], &bidder));
+        assert_ne!(commitment, state::sealed_bid_commitment(600_000, &salt, &test_pubkey(9)));
+
+        // Two tickets with a deposit above the hidden price
+        let mut bid = state::Bid {
+            bidder,
+            event: test_pubkey(20),
+            tier: 0,
+            amount: 2_000_000,
+            quantity: 2,
+            tickets_awarded: 0,
+            refunded_amount: 0,
+            settled: false,
+            commitment,
+            revealed_price: 0,
+            presale: false,
+            status: state::BidStatus::Pending,
+            bump: 252,
+        };
+
+        // Revealing sets the price the bid is awarded at
+        bid.revealed_price = 600_000;
+        state::transition(&mut bid.status, state::BidStatus::Revealed).unwrap();
+        assert_eq!(bid.unit_price(), Some(600_000));
+        assert!(state::transition(&mut bid.status, state::BidStatus::Revealed).is_err());
+
+        // Both tickets won, the lowest winning bid at 500_000 sets the uniform price
+        state::transition(&mut bid.status, state::BidStatus::Awarded).unwrap();
+        bid.tickets_awarded = 2;
+        assert_eq!(bid.refund_due(500_000), Some(1_000_000));
+
+        // A revealed bid that lost gets its whole deposit back
+        let mut losing_bid = bid.clone();
+        losing_bid.status = state::BidStatus::Revealed;
+        losing_bid.tickets_awarded = 0;
+        assert_eq!(losing_bid.refund_due(500_000), Some(2_000_000));
+        state::transition(&mut losing_bid.status, state::BidStatus::Refunded).unwrap();
+    }
+
+    #[test]
+    fn test_ticket_tiers() {
+        // Two VIP tickets, then eight general admission ones
+        let (event_key, mut event) = test_event_at_pda(test_pubkey(1), 0);
+        let tier = |metadata_url: &str, ticket_supply, start_price, end_price| state::TicketTier {
+            metadata_url: metadata_url.to_string(),
+            ticket_supply,
+            tickets_awarded: 0,
+            start_price,
+            end_price,
+            price_curve: state::PriceCurve::Linear,
+            lowest_awarded_bid: 0,
+            close_price: 0,
+            presale_tickets_awarded: 0,
+            presale_proceeds: 0,
+        };
+        event.tiers = vec![
+            tier("https://example.com/vip.json", 2, 3_000_000, 1_000_000),
+            tier("https://example.com/ga.json", 8, 1_000_000, 100_000),
+        ];
+        event.tickets_awarded = 0;
+        event.status = state::EventStatus::Active;
+        const { assert!(MAX_TIERS_PER_EVENT >= 2) };
+
+        // Tickets are numbered tier by tier
+        assert_eq!(event.tier_tickets(0).unwrap(), 0..2);
+        assert_eq!(event.tier_tickets(1).unwrap(), 2..10);
+        assert_eq!(event.tier_of_ticket(1), Some(0));
+        assert_eq!(event.tier_of_ticket(2), Some(1));
+        assert_eq!(event.tier_of_ticket(10), None);
+        assert_eq!(event.tier(2).unwrap_err(), ErrorCode::InvalidTier.into());
+
+        // Each tier runs down its own price curve
+        assert_eq!(event.get_current_auction_price(0, event.auction_start_time).unwrap(), 3_000_000);
+        assert_eq!(event.get_current_auction_price(1, event.auction_start_time).unwrap(), 1_000_000);
+        assert_eq!(event.get_current_auction_price(1, event.auction_end_time).unwrap(), 100_000);
+
+        // A tier's tickets can only be awarded from its own assets
+        let inventory = state::TicketInventory {
+            event: event_key,
+            page: 0,
+            asset_ids: (0..10).map(|seed| test_pubkey(100 + seed)).collect(),
+            awarded: vec![false; 10],
+            bump: 250,
+        };
+        assert_eq!(inventory.find_unawarded_asset(&test_pubkey(101), &event.tier_tickets(0).unwrap()).unwrap(), 1);
+        assert_eq!(
+            inventory.find_unawarded_asset(&test_pubkey(101), &event.tier_tickets(1).unwrap()).unwrap_err(),
+            ErrorCode::CnftAssetNotInTier.into()
+        );
+
+        // A sold-out tier leaves the others on sale
+        for amount in [3_000_000, 2_000_000] {
+            event.tiers[0].record_awarded_bid(amount);
+            event.record_awarded_ticket(0).unwrap();
+        }
+        assert_eq!(event.record_awarded_ticket(0).unwrap_err(), ErrorCode::TicketsSoldOut.into());
+        event.tiers[1].record_awarded_bid(500_000);
+        event.record_awarded_ticket(1).unwrap();
+        assert_eq!(event.tickets_awarded, 3);
+
+        // Each tier clears at its own lowest awarded bid, and proceeds add up across tiers
+        for tier in event.tiers.iter_mut() {
+            tier.close_price = tier.clearing_price();
+        }
+        assert_eq!(event.tiers[0].close_price, 2_000_000);
+        assert_eq!(event.tiers[1].close_price, 500_000);
+        assert_eq!(event.total_proceeds(), Some(2 * 2_000_000 + 500_000));
+        assert_eq!(event.max_resale_price(0).unwrap(), 2_200_000);
+        assert_eq!(event.max_resale_price(1).unwrap(), 550_000);
+
+        // A bid is only refunded through its own tier
+        let bidder = test_pubkey(8);
+        let (bid_key, bump) = Pubkey::find_program_address(&[b"bid", event_key.as_ref(), bidder.as_ref(), &[1]], &escrow::ID);
+        let (_, mut bid) = test_bid_at_pda(event_key, bidder);
+        bid.tier = 1;
+        bid.bump = bump;
+        assert_eq!(
+            refund_bid_accounts(bidder, (event_key, &event), (bid_key, &bid)).unwrap_err(),
+            ErrorCode::BidNotForTier.into()
+        );
+    }
+
+    #[test]
+    fn test_protocol_config() {
+        // The compile-time limits are the defaults, and no override can go past the hard caps
+        let mut limits = state::ProtocolLimits::default();
+        assert!(limits.is_valid());
+
+        // Events outside the limits are told which one they broke
+        assert!(limits.check_ticket_price(MIN_TICKET_PRICE).is_ok());
+        assert_eq!(limits.check_ticket_price(MIN_TICKET_PRICE - 1).unwrap_err(), ErrorCode::TicketPriceBelowMinimum.into());
+        assert!(limits.check_ticket_supply(MAX_TICKETS_PER_EVENT).is_ok());
+        assert_eq!(limits.check_ticket_supply(MAX_TICKETS_PER_EVENT + 1).unwrap_err(), ErrorCode::TicketSupplyAboveMaximum.into());
+        assert!(limits.check_auction(test_time(), test_time() + MIN_AUCTION_DURATION).is_ok());
+        assert_eq!(
+            limits.check_auction(test_time(), test_time() + MIN_AUCTION_DURATION - 1).unwrap_err(),
+            ErrorCode::AuctionDurationOutOfRange.into()
+        );
+        assert_eq!(
+            limits.check_auction(test_time(), test_time() + MAX_AUCTION_DURATION + 1).unwrap_err(),
+            ErrorCode::AuctionDurationOutOfRange.into()
+        );
+
+        limits.max_tickets_per_event = MAX_TICKETS_PER_EVENT + 1;
+        assert!(!limits.is_valid());
+        limits = state::ProtocolLimits { min_auction_duration: 3600, max_auction_duration: 60, ..Default::default() };
+        assert!(!limits.is_valid());
+        assert!(state::ProtocolConfig::is_valid_fee(MAX_PROTOCOL_FEE_BPS));
+        assert!(!state::ProtocolConfig::is_valid_fee(MAX_PROTOCOL_FEE_BPS + 1));
+
+        // Withdrawn proceeds are split at the fee the event was created with
+        let (_, mut event) = test_event_at_pda(test_pubkey(1), 0);
+        assert_eq!(event.protocol_fee(400_000), Some(0));
+        event.protocol_fee_bps = 250;
+        assert_eq!(event.protocol_fee(400_000), Some(10_000));
+
+        // Only the admin can change the fee, and only the proposed admin can take over
+        let admin = test_pubkey(30);
+        let pending_admin = test_pubkey(31);
+        let (config_key, bump) = Pubkey::find_program_address(&[b"protocol_config"], &escrow::ID);
+        let config = state::ProtocolConfig {
+            admin,
+            pending_admin: Some(pending_admin),
+            fee_bps: 250,
+            treasury: test_pubkey(32),
+            limits: state::ProtocolLimits::default(),
+            bump,
+        };
+        let set_fee_accounts = |signer: Pubkey| {
+            let mut accounts: &[AccountInfo<'static>] = Box::leak(Box::new([
+                wallet_account(signer, true),
+                program_account(config_key, &config),
+            ]));
+            SetProtocolFeeAccountConstraints::try_accounts(
+                &escrow::ID,
+                &mut accounts,
+                &[],
+                &mut SetProtocolFeeAccountConstraintsBumps::default(),
+                &mut BTreeSet::new(),
+            )
+            .map(|_| ())
+        };
+        let accept_admin_accounts = |signer: Pubkey| {
+            let mut accounts: &[AccountInfo<'static>] = Box::leak(Box::new([
+                wallet_account(signer, true),
+                program_account(config_key, &config),
+            ]));
+            AcceptProtocolAdminAccountConstraints::try_accounts(
+                &escrow::ID,
+                &mut accounts,
+                &[],
+                &mut AcceptProtocolAdminAccountConstraintsBumps::default(),
+                &mut BTreeSet::new(),
+            )
+            .map(|_| ())
+        };
+        assert!(set_fee_accounts(admin).is_ok());
+        assert_eq!(set_fee_accounts(pending_admin).unwrap_err(), ErrorCode::NotProtocolAdmin.into());
+        assert!(accept_admin_accounts(pending_admin).is_ok());
+        assert_eq!(accept_admin_accounts(admin).unwrap_err(), ErrorCode::NotPendingProtocolAdmin.into());
+    }
+
+    /// Build an initialized Token-2022 mint account with the given extensions
+    fn token_2022_mint_data(extensions: &[ExtensionType]) -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<MintState>(extensions).unwrap();
+        let mut data = vec![0u8; len];
+        let mut mint = StateWithExtensionsMut::<MintState>::unpack_uninitialized(&mut data).unwrap();
+        if extensions.contains(&ExtensionType::TransferFeeConfig) {
+            mint.init_extension::<TransferFeeConfig>(true).unwrap();
+        }
+        mint.base = MintState { decimals: 6, is_initialized: true, ..Default::default() };
+        mint.pack_base();
+        mint.init_account_type().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_payment_mints() {
+        // Plain SPL Token and Token-2022 mints deliver every token sent to the escrow
+        let mut spl_token_mint = vec![0u8; MintState::LEN];
+        MintState { decimals: 6, is_initialized: true, ..Default::default() }.pack_into_slice(&mut spl_token_mint);
+        assert!(is_supported_payment_mint(&spl_token_mint));
+        assert!(is_supported_payment_mint(&token_2022_mint_data(&[])));
+
+        // A transfer fee would leave the escrow short of what it recorded
+        assert!(!is_supported_payment_mint(&token_2022_mint_data(&[ExtensionType::TransferFeeConfig])));
+
+        // Anything that is not a mint is rejected
+        assert!(!is_supported_payment_mint(&[0u8; 10]));
+    }
+
+    #[test]
+    fn test_sealed_bid_ranking() {
+        // The ranking of a full-size event fits in a single 10 KB account creation
+        let (_, mut event) = test_event_at_pda(test_pubkey(1), 0);
+        event.tiers[0].ticket_supply = MAX_TICKETS_PER_EVENT;
+        assert!(8 + state::BidRanking::space(&event.tiers) <= 10_240);
+
+        // Fewer tickets revealed than the tier holds: every revealed bid wins
+        let mut ranking = state::TierRanking::default();
+        ranking.record_revealed_bid(400_000, 1, 3);
+        assert_eq!(ranking.cutoff_price(3), None);
+        assert!(ranking.can_award(400_000, 3));
+
+        // Only the three highest-priced tickets stay ranked, ties keeping their reveal order
+        ranking.record_revealed_bid(500_000, 1, 3);
+        ranking.record_revealed_bid(600_000, 2, 3);
+        ranking.record_revealed_bid(500_000, 1, 3);
+        ranking.record_revealed_bid(300_000, 1, 3);
+        assert_eq!(ranking.winning_prices, vec![600_000, 600_000, 500_000]);
+        assert_eq!(ranking.cutoff_price(3), Some(500_000));
+
+        // Bids below the cutoff are outranked
+        assert!(!ranking.can_award(400_000, 3));
+
+        // The organizer awards a bid at the cutoff price first, and the higher bid still wins both its tickets
+        assert!(ranking.can_award(500_000, 3));
+        ranking.record_award(500_000, 3).unwrap();
+        assert_eq!(ranking.cutoff_tickets_awarded, 1);
+        assert!(!ranking.can_award(500_000, 3));
+        assert!(ranking.can_award(600_000, 3));
+        ranking.record_award(600_000, 3).unwrap();
+        assert!(ranking.can_award(600_000, 3));
+        ranking.record_award(600_000, 3).unwrap();
+        assert_eq!(ranking.cutoff_tickets_awarded, 1);
+    }
+
+    #[test]
+    fn test_price_curves() {
+        let start_time = test_time();
+        let end_time = start_time + 3600;
+        let start_price = 1_000_000;
+        let end_price = 100_000;
+
+        // Exponential decay: the amount above the end price halves every 10 minutes
+        let exponential = state::PriceCurve::ExponentialDecay { half_life: 600 };
+        assert!(exponential.is_valid());
+        assert_eq!(exponential.price_at(start_price, end_price, start_time, end_time, start_time), start_price);
+        assert_eq!(exponential.price_at(start_price, end_price, start_time, end_time, start_time + 600), 550_000);
+        assert_eq!(exponential.price_at(start_price, end_price, start_time, end_time, start_time + 1200), 325_000);
+        assert_eq!(exponential.price_at(start_price, end_price, start_time, end_time, start_time + 300), 775_000);
+        assert_eq!(exponential.price_at(start_price, end_price, start_time, end_time, end_time), end_price);
+
+        // Stepwise: 10% of the start price off every 5 minutes, floored at the end price
+        let stepwise = state::PriceCurve::Stepwise { step_interval: 300, drop_bps: 1_000 };
+        assert!(stepwise.is_valid());
+        assert_eq!(stepwise.price_at(start_price, end_price, start_time, end_time, start_time + 299), start_price);
+        assert_eq!(stepwise.price_at(start_price, end_price, start_time, end_time, start_time + 300), 900_000);
+        assert_eq!(stepwise.price_at(start_price, end_price, start_time, end_time, start_time + 1500), 500_000);
+        assert_eq!(stepwise.price_at(start_price, end_price, start_time, end_time, start_time + 3000), end_price);
+
+        // Extreme inputs cannot overflow
+        let linear = state::PriceCurve::Linear;
+        assert_eq!(linear.price_at(u64::MAX, 0, i64::MIN, i64::MAX, 0), u64::MAX / 2);
+
+        // Curves that never move are rejected
+        assert!(!state::PriceCurve::ExponentialDecay { half_life: 0 }.is_valid());
+        assert!(!state::PriceCurve::Stepwise { step_interval: 0, drop_bps: 100 }.is_valid());
+        assert!(!state::PriceCurve::Stepwise { step_interval: 60, drop_bps: 0 }.is_valid());
     }
-}
\ No newline at end of file
+}